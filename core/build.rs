@@ -0,0 +1,54 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Generates the image ID tables of the header-chain and work-only guests from the
+// ELFs in `elfs/`, one per network.
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let elfs_dir = Path::new(&manifest_dir).join("../elfs");
+    println!("cargo:rerun-if-changed={}", elfs_dir.display());
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    for (suffix, table, file_name) in [
+        (
+            "-headerchain-guest",
+            "HEADER_CHAIN_METHOD_IDS",
+            "header_chain_method_ids.rs",
+        ),
+        (
+            "-work-only-guest",
+            "WORK_ONLY_METHOD_IDS",
+            "work_only_method_ids.rs",
+        ),
+    ] {
+        let entries = image_ids(&elfs_dir, suffix);
+        fs::write(
+            Path::new(&out_dir).join(file_name),
+            format!(
+                "pub static {}: &[(&str, [u32; 8])] = &[\n{}\n];\n",
+                table,
+                entries.join("\n")
+            ),
+        )
+        .expect("Failed to write image IDs");
+    }
+}
+
+/// Table entries of the `<network><suffix>` ELFs, sorted by network.
+fn image_ids(elfs_dir: &Path, suffix: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(elfs_dir).expect("Failed to read elfs directory") {
+        let path = entry.unwrap().path();
+        let file_name = path.file_name().unwrap().to_str().unwrap().to_owned();
+        let Some(network) = file_name.strip_suffix(suffix) else {
+            continue;
+        };
+        let elf = fs::read(&path).expect("Failed to read ELF");
+        let image_id = risc0_binfmt::compute_image_id(&elf)
+            .unwrap_or_else(|e| panic!("Invalid ELF {}: {}", file_name, e));
+        entries.push(format!("    ({:?}, {:?}),", network, image_id.as_words()));
+    }
+    entries.sort();
+    entries
+}
@@ -0,0 +1,82 @@
+use ark_bn254::Fr;
+use sha2::{Digest, Sha256};
+
+use crate::groth16::split_reversed_digest;
+use crate::journal::encode_journal;
+use crate::registry::{pinned_work_only_method_id, Risc0VerifierEntry};
+use crate::WorkOnlyCircuitOutput;
+
+// Receipt claim of the work-only guest, as verified by the winternitz guest. The
+// pre state is the work-only image ID, see `pre_state`.
+pub static POST_STATE: [u8; 32] =
+    hex_literal::hex!("a3acc27117418996340b84e5a90f3ef4c49d22c79e44aad822ec9c313e1eb8e2");
+pub static INPUT: [u8; 32] =
+    hex_literal::hex!("0000000000000000000000000000000000000000000000000000000000000000");
+pub static ASSUMPTIONS: [u8; 32] =
+    hex_literal::hex!("0000000000000000000000000000000000000000000000000000000000000000");
+pub static CLAIM_TAG: [u8; 32] =
+    hex_literal::hex!("cb1fefcd1f2d9a64975cbbbf6e161e2914434b0cbb9960b84df5d717e86b48af"); // hash of "risc0.ReceiptClaim"
+pub static OUTPUT_TAG: [u8; 32] =
+    hex_literal::hex!("77eafeb366a78b47747de0d7bb176284085ff5564887009a5be63da32d3559d4"); // hash of "risc0.Output"
+
+pub fn create_output_digest(journal: &[u8]) -> [u8; 32] {
+    let journal_digest: [u8; 32] = Sha256::digest(journal).into();
+    let len_output: [u8; 2] = hex::decode("0200").unwrap().try_into().unwrap();
+
+    let output_pre_digest: [u8; 98] = [
+        &OUTPUT_TAG,
+        &journal_digest[..],
+        &ASSUMPTIONS[..],
+        &len_output[..],
+    ]
+    .concat()
+    .try_into()
+    .expect("slice has correct length");
+
+    Sha256::digest(output_pre_digest).into()
+}
+
+/// Pre state of the work-only claim: the digest bytes of the work-only image ID
+/// of the build network, taken from `elfs/` by the build script.
+pub fn pre_state() -> [u8; 32] {
+    pinned_work_only_method_id()
+        .map(u32::to_le_bytes)
+        .concat()
+        .try_into()
+        .unwrap()
+}
+
+pub fn create_claim_digest(output_digest: &[u8; 32]) -> [u8; 32] {
+    let data: [u8; 8] = [0; 8];
+
+    let claim_len: [u8; 2] = [4, 0];
+
+    let concatenated = [
+        &CLAIM_TAG,
+        &INPUT,
+        &pre_state(),
+        &POST_STATE,
+        output_digest,
+        &data[..],
+        &claim_len,
+    ]
+    .concat();
+
+    let mut claim_digest = Sha256::digest(concatenated);
+    claim_digest.reverse();
+
+    claim_digest.into()
+}
+
+/// Groth16 public inputs of a work-only proof with `output` as its journal.
+pub fn work_only_public_inputs(
+    output: &WorkOnlyCircuitOutput,
+    verifier: &Risc0VerifierEntry,
+) -> Vec<Fr> {
+    let output_digest = create_output_digest(&encode_journal(output));
+    let claim_digest = create_claim_digest(&output_digest);
+    let (c0, c1) = split_reversed_digest(&claim_digest);
+
+    let (a0, a1) = verifier.control_root_fr();
+    vec![a0, a1, c0, c1, verifier.bn254_control_id_fr()]
+}
@@ -1,36 +1,19 @@
-use ark_bn254::Fr;
-use ark_ff::BigInt;
+/// Network the crate was built for, from `BITCOIN_NETWORK` at build time.
+pub const BITCOIN_NETWORK: &str = match option_env!("BITCOIN_NETWORK") {
+    Some(network) => network,
+    None => "regtest",
+};
 
-// GROTH16 RELATED CONSTANTS
-pub static PRE_STATE: [u8; 32] =
-    hex_literal::hex!("130e56990942f1792ae3ef2e44d30d40519acefd8e6bc279e650f4f64b24e208");
-pub static POST_STATE: [u8; 32] =
-    hex_literal::hex!("a3acc27117418996340b84e5a90f3ef4c49d22c79e44aad822ec9c313e1eb8e2");
-pub static INPUT: [u8; 32] =
-    hex_literal::hex!("0000000000000000000000000000000000000000000000000000000000000000");
-pub static ASSUMPTIONS: [u8; 32] =
-    hex_literal::hex!("0000000000000000000000000000000000000000000000000000000000000000");
-pub static CLAIM_TAG: [u8; 32] =
-    hex_literal::hex!("cb1fefcd1f2d9a64975cbbbf6e161e2914434b0cbb9960b84df5d717e86b48af"); // hash of "risc0.ReceiptClaim"
-pub static OUTPUT_TAG: [u8; 32] =
-    hex_literal::hex!("77eafeb366a78b47747de0d7bb176284085ff5564887009a5be63da32d3559d4"); // hash of "risc0.Output"
-
-pub const A0_BIGINT: BigInt<4> = BigInt::new([3584412468423285388, 5573840904707615506, 0, 0]);
-pub const A0_ARK: ark_ff::Fp<ark_ff::MontBackend<ark_bn254::FrConfig, 4>, 4> = Fr::new(A0_BIGINT);
-
-pub const A1_BIGINT: BigInt<4> = BigInt::new([3118573868620133879, 7567222285189782870, 0, 0]);
-pub const A1_ARK: ark_ff::Fp<ark_ff::MontBackend<ark_bn254::FrConfig, 4>, 4> = Fr::new(A1_BIGINT);
-
-pub const BN_254_CONTROL_ID_BIGINT: BigInt<4> = BigInt::new([
-    10066737433256753856,
-    15970898588890169697,
-    12996428817291790227,
-    307492062473808767,
-]);
-pub const BN_254_CONTROL_ID_ARK: ark_ff::Fp<ark_ff::MontBackend<ark_bn254::FrConfig, 4>, 4> =
-    Fr::new(BN_254_CONTROL_ID_BIGINT);
+/// P2P message start bytes of each network, committed by the work-only guest.
+pub const NETWORK_MAGICS: &[(&str, [u8; 4])] = &[
+    ("mainnet", hex_literal::hex!("f9beb4d9")),
+    ("testnet4", hex_literal::hex!("1c163f28")),
+    ("signet", hex_literal::hex!("0a03cf40")),
+    ("regtest", hex_literal::hex!("fabfb5da")),
+];
 
-pub const PREPARED_VK: &[u8] = &[
+// Prepared verifying key of the RISC Zero v1.2 Groth16 circuit.
+pub const RISC0_V1_2_PREPARED_VK: &[u8] = &[
     226, 242, 109, 190, 162, 153, 245, 34, 59, 100, 108, 177, 251, 51, 234, 219, 5, 157, 148, 7,
     85, 157, 116, 65, 223, 217, 2, 227, 167, 154, 77, 45, 38, 25, 77, 0, 255, 202, 118, 240, 1, 3,
     35, 25, 10, 131, 137, 206, 69, 227, 159, 32, 96, 236, 216, 97, 176, 206, 55, 60, 80, 221, 190,
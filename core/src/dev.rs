@@ -0,0 +1,112 @@
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, ProvingKey};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::groth16::CircuitGroth16Proof;
+use crate::registry::Risc0VerifierEntry;
+
+/// Seed of the dev trusted setup. Anyone can derive the proving key from it, so
+/// dev seals only exercise the pipeline and must never be accepted in production.
+const DEV_SETUP_SEED: u64 = 0x5754_4e5a_2d44_4556;
+
+/// Prepared verifying key of [`dev_proving_key`], checked by `test_dev_prepared_vk`.
+const DEV_PREPARED_VK: &[u8] = include_bytes!("dev_prepared_vk.bin");
+
+/// Test Groth16 setup accepted by guests built with the `dev-mode` feature, in
+/// place of the RISC Zero setup that wraps real receipts.
+pub static DEV_VERIFIER_ENTRY: Risc0VerifierEntry = Risc0VerifierEntry {
+    // "dev-control-root"
+    control_root: hex_literal::hex!(
+        "6465762d636f6e74726f6c2d726f6f7400000000000000000000000000000000"
+    ),
+    // "dev-bn254-control-id"
+    bn254_control_id: hex_literal::hex!(
+        "6465762d626e3235342d636f6e74726f6c2d6964000000000000000000000000"
+    ),
+    prepared_vk: DEV_PREPARED_VK,
+};
+
+/// Circuit with the five public inputs of the RISC Zero Groth16 verifier and no
+/// other statement, so a seal can be made for any claim.
+#[derive(Clone)]
+struct PublicInputsCircuit {
+    public_inputs: [Fr; 5],
+}
+
+impl ConstraintSynthesizer<Fr> for PublicInputsCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        for input in self.public_inputs {
+            let input = cs.new_input_variable(|| Ok(input))?;
+            cs.enforce_constraint(lc!() + input, lc!() + Variable::One, lc!() + input)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn dev_proving_key() -> ProvingKey<Bn254> {
+    let circuit = PublicInputsCircuit {
+        public_inputs: [Fr::from(0u64); 5],
+    };
+    Groth16::<Bn254>::generate_random_parameters_with_reduction(
+        circuit,
+        &mut StdRng::seed_from_u64(DEV_SETUP_SEED),
+    )
+    .expect("Dev setup of a fixed circuit can't fail")
+}
+
+/// Makes a dev seal for `public_inputs`, e.g. from
+/// [`crate::claim::work_only_public_inputs`] with [`DEV_VERIFIER_ENTRY`].
+pub fn dev_prove(public_inputs: &[Fr]) -> Result<CircuitGroth16Proof, SynthesisError> {
+    let circuit = PublicInputsCircuit {
+        public_inputs: public_inputs
+            .try_into()
+            .map_err(|_| SynthesisError::Unsatisfiable)?,
+    };
+    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(
+        circuit,
+        &dev_proving_key(),
+        &mut rand::thread_rng(),
+    )?;
+    Ok(CircuitGroth16Proof::new(proof.a, proof.b, proof.c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claim::work_only_public_inputs;
+    use crate::WorkOnlyCircuitOutput;
+    use ark_serialize::CanonicalSerialize;
+
+    #[test]
+    fn test_dev_prepared_vk() {
+        let pvk = ark_groth16::prepare_verifying_key(&dev_proving_key().vk);
+        let mut bytes = Vec::new();
+        pvk.serialize_uncompressed(&mut bytes).unwrap();
+        assert!(bytes == DEV_PREPARED_VK, "Regenerate dev_prepared_vk.bin");
+    }
+
+    #[test]
+    fn test_dev_prove() {
+        let output = WorkOnlyCircuitOutput {
+            work_u128: [1, 2, 3, 4],
+            best_block_hash: [5; 32],
+            block_height: 6,
+            header_chain_method_id: [7; 8],
+            network_magic: [8; 4],
+            policy_digest: [9; 32],
+        };
+        let public_inputs = work_only_public_inputs(&output, &DEV_VERIFIER_ENTRY);
+        let proof = dev_prove(&public_inputs).unwrap();
+
+        let pvk = DEV_VERIFIER_ENTRY.prepared_vk().unwrap();
+        assert!(Groth16::<Bn254>::verify_proof(&pvk, &proof.into(), &public_inputs).unwrap());
+
+        let mut other_inputs = public_inputs.clone();
+        other_inputs[2] += Fr::from(1u64);
+        assert!(!Groth16::<Bn254>::verify_proof(&pvk, &proof.into(), &other_inputs).unwrap());
+        assert!(dev_prove(&public_inputs[..4]).is_err());
+    }
+}
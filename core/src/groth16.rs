@@ -1,10 +1,79 @@
-use ark_bn254::Bn254;
-use ark_ff::{Field, PrimeField};
-use ark_groth16::Proof;
+use ark_bn254::{Bn254, Fq, Fq2, Fr};
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
+use ark_relations::r1cs::SynthesisError;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError};
+use borsh::{BorshDeserialize, BorshSerialize};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
 type G1 = ark_bn254::G1Affine;
 type G2 = ark_bn254::G2Affine;
 
+/// Size of gnark's raw (uncompressed) bn254 Groth16 proof encoding without commitments:
+/// `Ar || Bs || Krs || len(Commitments) || CommitmentPok`.
+pub const GNARK_PROOF_SIZE: usize = 64 + 128 + 64 + 4 + 64;
+
+/// gnark flag for an uncompressed point at infinity, set in the top bits of the first byte.
+const GNARK_UNCOMPRESSED_INFINITY: u8 = 0b01 << 6;
+
+/// BitVM flag for a point whose `y` is the larger of the two square roots.
+const BITVM_Y_IS_GREATEST: u8 = 0b10 << 6;
+/// BitVM flag for the point at infinity.
+const BITVM_INFINITY: u8 = 0b01 << 6;
+
+/// Selector of `IRiscZeroVerifier.verify(bytes,bytes32,bytes32)`.
+pub const EVM_VERIFY_SELECTOR: [u8; 4] = [0xab, 0x75, 0x0e, 0x75];
+
+/// Size of the EVM seal: the verifier selector followed by the uncompressed seal.
+const EVM_SEAL_SIZE: usize = 4 + 256;
+/// Offset of the seal's data, after the three head words of `verify`.
+const EVM_SEAL_OFFSET: usize = 3 * 32;
+/// `verify` calldata: selector, head, seal length and the seal padded to 32 bytes.
+const EVM_CALLDATA_SIZE: usize = 4 + EVM_SEAL_OFFSET + 32 + EVM_SEAL_SIZE.div_ceil(32) * 32;
+
+/// Arguments of an `IRiscZeroVerifier.verify` call.
+#[derive(Debug, Clone, Copy)]
+pub struct EvmVerifyCall {
+    /// Verifier selector at the start of the seal.
+    pub selector: [u8; 4],
+    pub proof: CircuitGroth16Proof,
+    pub image_id: [u8; 32],
+    pub journal_digest: [u8; 32],
+}
+
+/// Big-endian ABI word of `value`.
+fn abi_word(value: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+/// Byte layout of the 128-byte compressed seal `a || b || c`.
+#[derive(
+    Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug, BorshDeserialize, BorshSerialize,
+)]
+pub enum CompressedSealEncoding {
+    /// arkworks' `serialize_compressed`: little-endian coordinates, G2 as `c0 || c1`,
+    /// flags in the top bits of the last byte.
+    Ark,
+    /// Layout of BitVM's Groth16 verifier: big-endian coordinates, G2 as `c1 || c0`,
+    /// flags in the top bits of the first byte.
+    BitVm,
+}
+
+/// Groth16 proof in the `proof.json` layout produced by snarkjs.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct SnarkjsProof {
+    pub pi_a: [String; 3],
+    pub pi_b: [[String; 2]; 3],
+    pub pi_c: [String; 3],
+    pub protocol: String,
+    pub curve: String,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct CircuitGroth16Proof {
     a: G1,
@@ -77,6 +146,209 @@ impl CircuitGroth16Proof {
         Ok(compressed)
     }
 
+    pub fn to_compressed_with(
+        &self,
+        encoding: CompressedSealEncoding,
+    ) -> Result<[u8; 128], SerializationError> {
+        match encoding {
+            CompressedSealEncoding::Ark => self.to_compressed(),
+            CompressedSealEncoding::BitVm => {
+                let mut compressed = [0u8; 128];
+                compressed[0..32].copy_from_slice(&bitvm_compress_g1(&self.a));
+                compressed[32..96].copy_from_slice(&bitvm_compress_g2(&self.b));
+                compressed[96..128].copy_from_slice(&bitvm_compress_g1(&self.c));
+                Ok(compressed)
+            }
+        }
+    }
+
+    pub fn from_compressed_with(
+        compressed: &[u8; 128],
+        encoding: CompressedSealEncoding,
+    ) -> Result<CircuitGroth16Proof, SerializationError> {
+        match encoding {
+            CompressedSealEncoding::Ark => CircuitGroth16Proof::from_compressed(compressed),
+            CompressedSealEncoding::BitVm => {
+                let a = bitvm_decompress_g1(compressed[0..32].try_into().unwrap())?;
+                let b = bitvm_decompress_g2(compressed[32..96].try_into().unwrap())?;
+                let c = bitvm_decompress_g1(compressed[96..128].try_into().unwrap())?;
+                Ok(CircuitGroth16Proof::new(a, b, c))
+            }
+        }
+    }
+
+    /// Inverse of [`CircuitGroth16Proof::from_seal`]: the 256-byte uncompressed risc0 seal.
+    pub fn to_seal(&self) -> [u8; 256] {
+        let mut seal = [0u8; 256];
+        seal[0..32].copy_from_slice(&fq_to_be_bytes(&self.a.x));
+        seal[32..64].copy_from_slice(&fq_to_be_bytes(&self.a.y));
+        seal[64..96].copy_from_slice(&fq_to_be_bytes(&self.b.x.c1));
+        seal[96..128].copy_from_slice(&fq_to_be_bytes(&self.b.x.c0));
+        seal[128..160].copy_from_slice(&fq_to_be_bytes(&self.b.y.c1));
+        seal[160..192].copy_from_slice(&fq_to_be_bytes(&self.b.y.c0));
+        seal[192..224].copy_from_slice(&fq_to_be_bytes(&self.c.x));
+        seal[224..256].copy_from_slice(&fq_to_be_bytes(&self.c.y));
+        seal
+    }
+
+    /// `seal` argument of risc0-ethereum's `RiscZeroGroth16Verifier.verify`: the
+    /// verifier selector followed by the uncompressed seal. Not ABI-encoded.
+    pub fn to_evm_seal(&self, selector: &[u8; 4]) -> Vec<u8> {
+        [&selector[..], &self.to_seal()[..]].concat()
+    }
+
+    /// Returns the verifier selector and the proof encoded in an EVM verifier seal.
+    pub fn from_evm_seal(
+        evm_seal: &[u8],
+    ) -> Result<([u8; 4], CircuitGroth16Proof), SerializationError> {
+        if evm_seal.len() != 4 + 256 {
+            return Err(SerializationError::InvalidData);
+        }
+        let selector: [u8; 4] = evm_seal[0..4].try_into().unwrap();
+        let seal: [u8; 256] = evm_seal[4..].try_into().unwrap();
+        Ok((selector, CircuitGroth16Proof::from_seal_checked(&seal)?))
+    }
+
+    /// ABI-encoded call of `IRiscZeroVerifier.verify(bytes seal, bytes32 imageId,
+    /// bytes32 journalDigest)`, with [`CircuitGroth16Proof::to_evm_seal`] as the seal.
+    pub fn to_evm_calldata(
+        &self,
+        selector: &[u8; 4],
+        image_id: &[u8; 32],
+        journal_digest: &[u8; 32],
+    ) -> Vec<u8> {
+        let seal = self.to_evm_seal(selector);
+        let mut calldata = Vec::with_capacity(EVM_CALLDATA_SIZE);
+        calldata.extend_from_slice(&EVM_VERIFY_SELECTOR);
+        calldata.extend_from_slice(&abi_word(EVM_SEAL_OFFSET));
+        calldata.extend_from_slice(image_id);
+        calldata.extend_from_slice(journal_digest);
+        calldata.extend_from_slice(&abi_word(seal.len()));
+        calldata.extend_from_slice(&seal);
+        calldata.resize(EVM_CALLDATA_SIZE, 0);
+        calldata
+    }
+
+    /// Inverse of [`CircuitGroth16Proof::to_evm_calldata`]. Rejects any other
+    /// function, non-canonical offsets and lengths, and non-zero padding.
+    pub fn from_evm_calldata(calldata: &[u8]) -> Result<EvmVerifyCall, SerializationError> {
+        if calldata.len() != EVM_CALLDATA_SIZE
+            || calldata[0..4] != EVM_VERIFY_SELECTOR
+            || calldata[4..36] != abi_word(EVM_SEAL_OFFSET)
+            || calldata[100..132] != abi_word(EVM_SEAL_SIZE)
+            || calldata[132 + EVM_SEAL_SIZE..]
+                .iter()
+                .any(|&byte| byte != 0)
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        let (selector, proof) =
+            CircuitGroth16Proof::from_evm_seal(&calldata[132..132 + EVM_SEAL_SIZE])?;
+        Ok(EvmVerifyCall {
+            selector,
+            proof,
+            image_id: calldata[36..68].try_into().unwrap(),
+            journal_digest: calldata[68..100].try_into().unwrap(),
+        })
+    }
+
+    /// Same as [`CircuitGroth16Proof::from_seal`], but rejects points that are not on
+    /// the curve or not in the prime order subgroup instead of panicking.
+    pub fn from_seal_checked(seal: &[u8; 256]) -> Result<CircuitGroth16Proof, SerializationError> {
+        let fq = |range: std::ops::Range<usize>| fq_from_be_bytes(&seal[range]);
+
+        let a = g1_from_coords(fq(0..32)?, fq(32..64)?)?;
+        let b = g2_from_coords(
+            Fq2::new(fq(96..128)?, fq(64..96)?),
+            Fq2::new(fq(160..192)?, fq(128..160)?),
+        )?;
+        let c = g1_from_coords(fq(192..224)?, fq(224..256)?)?;
+
+        Ok(CircuitGroth16Proof::new(a, b, c))
+    }
+
+    pub fn to_snarkjs(&self) -> SnarkjsProof {
+        SnarkjsProof {
+            pi_a: [
+                fq_to_decimal(&self.a.x),
+                fq_to_decimal(&self.a.y),
+                "1".to_string(),
+            ],
+            pi_b: [
+                [fq_to_decimal(&self.b.x.c0), fq_to_decimal(&self.b.x.c1)],
+                [fq_to_decimal(&self.b.y.c0), fq_to_decimal(&self.b.y.c1)],
+                ["1".to_string(), "0".to_string()],
+            ],
+            pi_c: [
+                fq_to_decimal(&self.c.x),
+                fq_to_decimal(&self.c.y),
+                "1".to_string(),
+            ],
+            protocol: "groth16".to_string(),
+            curve: "bn128".to_string(),
+        }
+    }
+
+    /// Reads a snarkjs proof. Only affine points (`z = 1`) are accepted, which is what
+    /// snarkjs writes for valid proofs.
+    pub fn from_snarkjs(proof: &SnarkjsProof) -> Result<CircuitGroth16Proof, SerializationError> {
+        if proof.protocol != "groth16"
+            || proof.curve != "bn128"
+            || proof.pi_a[2] != "1"
+            || proof.pi_c[2] != "1"
+            || proof.pi_b[2] != ["1", "0"]
+        {
+            return Err(SerializationError::InvalidData);
+        }
+
+        let a = g1_from_coords(
+            fq_from_decimal(&proof.pi_a[0])?,
+            fq_from_decimal(&proof.pi_a[1])?,
+        )?;
+        let b = g2_from_coords(
+            Fq2::new(
+                fq_from_decimal(&proof.pi_b[0][0])?,
+                fq_from_decimal(&proof.pi_b[0][1])?,
+            ),
+            Fq2::new(
+                fq_from_decimal(&proof.pi_b[1][0])?,
+                fq_from_decimal(&proof.pi_b[1][1])?,
+            ),
+        )?;
+        let c = g1_from_coords(
+            fq_from_decimal(&proof.pi_c[0])?,
+            fq_from_decimal(&proof.pi_c[1])?,
+        )?;
+
+        Ok(CircuitGroth16Proof::new(a, b, c))
+    }
+
+    /// gnark's raw bn254 proof encoding (`Proof.WriteRawTo`) with no commitments.
+    /// The point layout matches the risc0 seal; gnark appends an empty commitment list
+    /// and a commitment proof of knowledge at infinity.
+    pub fn to_gnark_bytes(&self) -> [u8; GNARK_PROOF_SIZE] {
+        let mut bytes = [0u8; GNARK_PROOF_SIZE];
+        bytes[0..256].copy_from_slice(&self.to_seal());
+        // 4-byte big-endian commitment count, left at zero.
+        bytes[260] = GNARK_UNCOMPRESSED_INFINITY;
+        bytes
+    }
+
+    /// Reads gnark's raw proof encoding. Proofs with commitments are rejected since
+    /// the RISC Zero circuit does not use them.
+    pub fn from_gnark_bytes(bytes: &[u8]) -> Result<CircuitGroth16Proof, SerializationError> {
+        if bytes.len() != GNARK_PROOF_SIZE {
+            return Err(SerializationError::InvalidData);
+        }
+        let commitment_count = u32::from_be_bytes(bytes[256..260].try_into().unwrap());
+        let mut infinity = [0u8; 64];
+        infinity[0] = GNARK_UNCOMPRESSED_INFINITY;
+        if commitment_count != 0 || bytes[260..] != infinity {
+            return Err(SerializationError::InvalidData);
+        }
+        CircuitGroth16Proof::from_seal_checked(bytes[0..256].try_into().unwrap())
+    }
+
     pub fn a(&self) -> &G1 {
         &self.a
     }
@@ -90,6 +362,220 @@ impl CircuitGroth16Proof {
     }
 }
 
+fn fq_to_be_bytes(fq: &Fq) -> [u8; 32] {
+    fq.into_bigint()
+        .to_bytes_be()
+        .try_into()
+        .expect("Fq is 32 bytes")
+}
+
+fn fq_from_be_bytes(bytes: &[u8]) -> Result<Fq, SerializationError> {
+    let fq = Fq::from_be_bytes_mod_order(bytes);
+    // Reject non-canonical encodings instead of silently reducing them.
+    if fq_to_be_bytes(&fq)[..] != *bytes {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(fq)
+}
+
+fn fq_to_decimal(fq: &Fq) -> String {
+    BigUint::from(fq.into_bigint()).to_str_radix(10)
+}
+
+fn fq_from_decimal(s: &str) -> Result<Fq, SerializationError> {
+    let fq = Fq::from_str(s).map_err(|_| SerializationError::InvalidData)?;
+    if fq_to_decimal(&fq) != s {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(fq)
+}
+
+fn bitvm_flags(infinity: bool, y_is_greatest: bool) -> u8 {
+    match (infinity, y_is_greatest) {
+        (true, _) => BITVM_INFINITY,
+        (false, true) => BITVM_Y_IS_GREATEST,
+        (false, false) => 0,
+    }
+}
+
+fn bitvm_compress_g1(point: &G1) -> [u8; 32] {
+    let mut bytes = fq_to_be_bytes(&point.x);
+    bytes[0] |= bitvm_flags(point.infinity, point.y > -point.y);
+    bytes
+}
+
+fn bitvm_compress_g2(point: &G2) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    bytes[0..32].copy_from_slice(&fq_to_be_bytes(&point.x.c1));
+    bytes[32..64].copy_from_slice(&fq_to_be_bytes(&point.x.c0));
+    bytes[0] |= bitvm_flags(point.infinity, point.y > -point.y);
+    bytes
+}
+
+/// Splits the flags off a BitVM encoded point, returning `(infinity, y_is_greatest)`.
+fn bitvm_take_flags(first_byte: &mut u8) -> Result<(bool, bool), SerializationError> {
+    let flags = *first_byte & (BITVM_INFINITY | BITVM_Y_IS_GREATEST);
+    *first_byte &= !flags;
+    match flags {
+        0 => Ok((false, false)),
+        BITVM_Y_IS_GREATEST => Ok((false, true)),
+        BITVM_INFINITY => Ok((true, false)),
+        _ => Err(SerializationError::UnexpectedFlags),
+    }
+}
+
+fn bitvm_decompress_g1(bytes: &[u8; 32]) -> Result<G1, SerializationError> {
+    let mut bytes = *bytes;
+    let (infinity, y_is_greatest) = bitvm_take_flags(&mut bytes[0])?;
+    let x = fq_from_be_bytes(&bytes)?;
+    if infinity {
+        return match x.is_zero() {
+            true => Ok(G1::identity()),
+            false => Err(SerializationError::InvalidData),
+        };
+    }
+    let point =
+        G1::get_point_from_x_unchecked(x, y_is_greatest).ok_or(SerializationError::InvalidData)?;
+    g1_from_coords(point.x, point.y)
+}
+
+fn bitvm_decompress_g2(bytes: &[u8; 64]) -> Result<G2, SerializationError> {
+    let mut bytes = *bytes;
+    let (infinity, y_is_greatest) = bitvm_take_flags(&mut bytes[0])?;
+    let x = Fq2::new(
+        fq_from_be_bytes(&bytes[32..64])?,
+        fq_from_be_bytes(&bytes[0..32])?,
+    );
+    if infinity {
+        return match x.is_zero() {
+            true => Ok(G2::identity()),
+            false => Err(SerializationError::InvalidData),
+        };
+    }
+    let point =
+        G2::get_point_from_x_unchecked(x, y_is_greatest).ok_or(SerializationError::InvalidData)?;
+    g2_from_coords(point.x, point.y)
+}
+
+fn g1_from_coords(x: Fq, y: Fq) -> Result<G1, SerializationError> {
+    let point = if x.is_zero() && y.is_zero() {
+        G1::identity()
+    } else {
+        G1::new_unchecked(x, y)
+    };
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(point)
+}
+
+fn g2_from_coords(x: Fq2, y: Fq2) -> Result<G2, SerializationError> {
+    let point = if x.is_zero() && y.is_zero() {
+        G2::identity()
+    } else {
+        G2::new_unchecked(x, y)
+    };
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(point)
+}
+
+/// Verifies several Groth16 proofs against the same verifying key with one
+/// multi-pairing, by checking a random linear combination of their verification
+/// equations. The coefficients are derived from a hash of every proof and public
+/// input, so the prover can't pick them after the fact.
+pub fn batch_verify(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proofs: &[(CircuitGroth16Proof, Vec<Fr>)],
+) -> Result<bool, SynthesisError> {
+    if proofs.is_empty() {
+        return Ok(false);
+    }
+
+    let coefficients = batch_coefficients(proofs)?;
+
+    let mut g1s: Vec<<Bn254 as Pairing>::G1Prepared> = Vec::with_capacity(proofs.len() + 2);
+    let mut g2s: Vec<<Bn254 as Pairing>::G2Prepared> = Vec::with_capacity(proofs.len() + 2);
+    let mut prepared_inputs = ark_bn254::G1Projective::zero();
+    let mut c = ark_bn254::G1Projective::zero();
+    let mut coefficient_sum = Fr::zero();
+
+    for ((proof, public_inputs), r) in proofs.iter().zip(coefficients) {
+        let proof_inputs = Groth16::<Bn254>::prepare_inputs(pvk, public_inputs)?;
+        g1s.push((proof.a * r).into());
+        g2s.push(proof.b.into());
+        prepared_inputs += proof_inputs * r;
+        c += proof.c * r;
+        coefficient_sum += r;
+    }
+
+    g1s.push(prepared_inputs.into());
+    g2s.push(pvk.gamma_g2_neg_pc.clone());
+    g1s.push(c.into());
+    g2s.push(pvk.delta_g2_neg_pc.clone());
+
+    let qap = <Bn254 as Pairing>::multi_miller_loop(g1s, g2s);
+    let test =
+        <Bn254 as Pairing>::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
+
+    Ok(test.0 == pvk.alpha_g1_beta_g2.pow(coefficient_sum.into_bigint()))
+}
+
+fn batch_coefficients(
+    proofs: &[(CircuitGroth16Proof, Vec<Fr>)],
+) -> Result<Vec<Fr>, SynthesisError> {
+    let mut transcript = Sha256::new();
+    for (proof, public_inputs) in proofs {
+        transcript.update(proof.to_seal());
+        for input in public_inputs {
+            let mut bytes = Vec::with_capacity(32);
+            input
+                .serialize_uncompressed(&mut bytes)
+                .map_err(|_| SynthesisError::AssignmentMissing)?;
+            transcript.update(bytes);
+        }
+    }
+    let seed: [u8; 32] = transcript.finalize().into();
+
+    // The first coefficient can be fixed to one without losing soundness.
+    Ok((0..proofs.len() as u64)
+        .map(|i| match i {
+            0 => Fr::one(),
+            _ => {
+                Fr::from_le_bytes_mod_order(&Sha256::digest([&seed[..], &i.to_le_bytes()].concat()))
+            }
+        })
+        .collect())
+}
+
+/// Splits a RISC Zero digest into the two 128-bit field elements used as Groth16
+/// public inputs, matching `risc0_groth16::split_digest`.
+pub fn split_digest(digest: &[u8; 32]) -> (Fr, Fr) {
+    let mut reversed = *digest;
+    reversed.reverse();
+    split_reversed_digest(&reversed)
+}
+
+/// Same as [`split_digest`], for a digest whose bytes are already reversed
+/// (e.g. the claim digest computed by the winternitz guest).
+pub fn split_reversed_digest(reversed: &[u8; 32]) -> (Fr, Fr) {
+    (
+        Fr::from_be_bytes_mod_order(&reversed[16..32]),
+        Fr::from_be_bytes_mod_order(&reversed[0..16]),
+    )
+}
+
+/// Returns the two control root limbs (`a0`, `a1`) of the RISC Zero Groth16 statement.
+pub fn control_root_to_fr(control_root: &[u8; 32]) -> (Fr, Fr) {
+    split_digest(control_root)
+}
+
+/// Returns the BN254 control ID as the last public input of the RISC Zero Groth16 statement.
+pub fn control_id_to_fr(control_id: &[u8; 32]) -> Fr {
+    Fr::from_le_bytes_mod_order(control_id)
+}
+
 impl From<CircuitGroth16Proof> for Proof<Bn254> {
     fn from(g16_seal: CircuitGroth16Proof) -> Self {
         Proof::<Bn254> {
@@ -103,9 +589,16 @@ impl From<CircuitGroth16Proof> for Proof<Bn254> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_ff::{One, UniformRand, Zero};
+    use crate::utils::to_decimal;
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInt, One, UniformRand, Zero};
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef},
+    };
     use ark_std::rand::Rng;
     use ark_std::test_rng;
+    use std::str::FromStr;
 
     fn random_g1() -> G1 {
         let mut rng = test_rng();
@@ -137,7 +630,7 @@ mod tests {
             let compressed = proof.to_compressed().expect("Compression failed");
             let decompressed_proof =
                 CircuitGroth16Proof::from_compressed(&compressed).expect("Decompression failed");
-    
+
             assert_eq!(proof.a(), decompressed_proof.a());
             assert_eq!(proof.b(), decompressed_proof.b());
             assert_eq!(proof.c(), decompressed_proof.c());
@@ -153,4 +646,364 @@ mod tests {
         assert_eq!(proof.b(), &groth16_proof.b);
         assert_eq!(proof.c(), &groth16_proof.c);
     }
+
+    #[test]
+    fn test_compressed_encodings_round_trip() {
+        for encoding in [CompressedSealEncoding::Ark, CompressedSealEncoding::BitVm] {
+            for _ in 0..16 {
+                let proof = CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1());
+
+                let compressed = proof.to_compressed_with(encoding).unwrap();
+                let decompressed =
+                    CircuitGroth16Proof::from_compressed_with(&compressed, encoding).unwrap();
+
+                assert_eq!(proof.a(), decompressed.a());
+                assert_eq!(proof.b(), decompressed.b());
+                assert_eq!(proof.c(), decompressed.c());
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitvm_encoding_layout() {
+        let proof = CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1());
+        let compressed = proof
+            .to_compressed_with(CompressedSealEncoding::BitVm)
+            .unwrap();
+        let seal = proof.to_seal();
+
+        let flags = |byte: u8| byte & 0b1100_0000;
+        let mut unflagged = compressed;
+        for i in [0, 32, 96] {
+            unflagged[i] &= 0b0011_1111;
+        }
+
+        // x coordinates are big-endian with the G2 coefficients in c1, c0 order,
+        // which is the same as in the uncompressed seal.
+        assert_eq!(unflagged[0..32], seal[0..32]);
+        assert_eq!(unflagged[32..96], seal[64..128]);
+        assert_eq!(unflagged[96..128], seal[192..224]);
+
+        let a_flag = if proof.a().y > -proof.a().y {
+            0b1000_0000
+        } else {
+            0
+        };
+        let b_flag = if proof.b().y > -proof.b().y {
+            0b1000_0000
+        } else {
+            0
+        };
+        assert_eq!(flags(compressed[0]), a_flag);
+        assert_eq!(flags(compressed[32]), b_flag);
+
+        let mut invalid = compressed;
+        invalid[0] |= 0b1100_0000;
+        assert!(
+            CircuitGroth16Proof::from_compressed_with(&invalid, CompressedSealEncoding::BitVm)
+                .is_err()
+        );
+    }
+
+    /// BitVM encodings of the EIP-197 generators, their negations and the points
+    /// at infinity, written out independently of the implementation.
+    #[test]
+    fn test_bitvm_reference_vectors() {
+        let g1 = G1::generator();
+        let g2 = G2::generator();
+        let g1_vectors: [(G1, [u8; 32]); 3] = [
+            (
+                g1,
+                hex_literal::hex!(
+                    "0000000000000000000000000000000000000000000000000000000000000001"
+                ),
+            ),
+            (
+                -g1,
+                hex_literal::hex!(
+                    "8000000000000000000000000000000000000000000000000000000000000001"
+                ),
+            ),
+            (
+                G1::identity(),
+                hex_literal::hex!(
+                    "4000000000000000000000000000000000000000000000000000000000000000"
+                ),
+            ),
+        ];
+        let g2_vectors: [(G2, [u8; 64]); 3] = [
+            (
+                g2,
+                hex_literal::hex!(
+                    "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"
+                    "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"
+                ),
+            ),
+            (
+                -g2,
+                hex_literal::hex!(
+                    "998e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"
+                    "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"
+                ),
+            ),
+            (
+                G2::identity(),
+                hex_literal::hex!(
+                    "4000000000000000000000000000000000000000000000000000000000000000"
+                    "0000000000000000000000000000000000000000000000000000000000000000"
+                ),
+            ),
+        ];
+
+        for (point, expected) in g1_vectors {
+            assert_eq!(bitvm_compress_g1(&point), expected);
+            assert_eq!(bitvm_decompress_g1(&expected).unwrap(), point);
+        }
+        for (point, expected) in g2_vectors {
+            assert_eq!(bitvm_compress_g2(&point), expected);
+            assert_eq!(bitvm_decompress_g2(&expected).unwrap(), point);
+        }
+
+        let proof = CircuitGroth16Proof::new(g1, -g2, G1::identity());
+        let compressed = proof
+            .to_compressed_with(CompressedSealEncoding::BitVm)
+            .unwrap();
+        assert_eq!(compressed[0..32], g1_vectors[0].1);
+        assert_eq!(compressed[32..96], g2_vectors[1].1);
+        assert_eq!(compressed[96..128], g1_vectors[2].1);
+    }
+
+    #[test]
+    fn test_to_seal_and_from_seal() {
+        for _ in 0..16 {
+            let proof = CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1());
+
+            let seal = proof.to_seal();
+            let from_seal = CircuitGroth16Proof::from_seal(&seal);
+            let from_seal_checked =
+                CircuitGroth16Proof::from_seal_checked(&seal).expect("Invalid seal");
+
+            assert_eq!(proof.a(), from_seal.a());
+            assert_eq!(proof.b(), from_seal.b());
+            assert_eq!(proof.c(), from_seal.c());
+            assert_eq!(from_seal_checked.to_seal(), seal);
+        }
+    }
+
+    #[test]
+    fn test_from_seal_checked_rejects_invalid_points() {
+        let mut seal = CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1()).to_seal();
+        seal[63] ^= 1;
+        assert!(CircuitGroth16Proof::from_seal_checked(&seal).is_err());
+    }
+
+    #[test]
+    fn test_to_evm_seal_and_from_evm_seal() {
+        let proof = CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1());
+        let selector = [0x31, 0x0f, 0xe5, 0x98];
+
+        let evm_seal = proof.to_evm_seal(&selector);
+        assert_eq!(evm_seal.len(), 260);
+        let (decoded_selector, decoded) =
+            CircuitGroth16Proof::from_evm_seal(&evm_seal).expect("Invalid seal");
+
+        assert_eq!(decoded_selector, selector);
+        assert_eq!(decoded.to_seal(), proof.to_seal());
+        assert!(CircuitGroth16Proof::from_evm_seal(&evm_seal[4..]).is_err());
+    }
+
+    #[test]
+    fn test_to_evm_calldata_and_from_evm_calldata() {
+        let proof = CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1());
+        let selector = [0x31, 0x0f, 0xe5, 0x98];
+        let image_id = [1u8; 32];
+        let journal_digest = [2u8; 32];
+
+        let calldata = proof.to_evm_calldata(&selector, &image_id, &journal_digest);
+        assert_eq!(calldata.len(), 4 + 4 * 32 + 288);
+        assert_eq!(calldata[0..4], hex_literal::hex!("ab750e75"));
+        assert_eq!(calldata[35], 0x60);
+        assert_eq!(calldata[36..68], image_id);
+        assert_eq!(calldata[68..100], journal_digest);
+        assert_eq!(calldata[100..132], abi_word(4 + 256));
+        assert_eq!(calldata[132..392], proof.to_evm_seal(&selector));
+        assert!(calldata[392..].iter().all(|&byte| byte == 0));
+
+        let call = CircuitGroth16Proof::from_evm_calldata(&calldata).expect("Invalid calldata");
+        assert_eq!(call.selector, selector);
+        assert_eq!(call.proof.to_seal(), proof.to_seal());
+        assert_eq!(call.image_id, image_id);
+        assert_eq!(call.journal_digest, journal_digest);
+
+        for (index, value) in [(0, 0), (35, 0x40), (131, 0), (419, 1)] {
+            let mut bad = calldata.clone();
+            bad[index] = value;
+            assert!(CircuitGroth16Proof::from_evm_calldata(&bad).is_err());
+        }
+        assert!(CircuitGroth16Proof::from_evm_calldata(&calldata[..419]).is_err());
+    }
+
+    #[test]
+    fn test_to_snarkjs_and_from_snarkjs() {
+        for _ in 0..16 {
+            let proof = CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1());
+
+            let json = serde_json::to_string(&proof.to_snarkjs()).unwrap();
+            let snarkjs: SnarkjsProof = serde_json::from_str(&json).unwrap();
+            let decoded = CircuitGroth16Proof::from_snarkjs(&snarkjs).expect("Invalid proof");
+
+            assert_eq!(proof.a(), decoded.a());
+            assert_eq!(proof.b(), decoded.b());
+            assert_eq!(proof.c(), decoded.c());
+        }
+
+        let mut snarkjs =
+            CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1()).to_snarkjs();
+        snarkjs.curve = "bls12381".to_string();
+        assert!(CircuitGroth16Proof::from_snarkjs(&snarkjs).is_err());
+    }
+
+    #[test]
+    fn test_to_gnark_bytes_and_from_gnark_bytes() {
+        for _ in 0..16 {
+            let proof = CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1());
+
+            let bytes = proof.to_gnark_bytes();
+            let decoded = CircuitGroth16Proof::from_gnark_bytes(&bytes).expect("Invalid proof");
+
+            assert_eq!(proof.a(), decoded.a());
+            assert_eq!(proof.b(), decoded.b());
+            assert_eq!(proof.c(), decoded.c());
+        }
+
+        let mut bytes =
+            CircuitGroth16Proof::new(random_g1(), random_g2(), random_g1()).to_gnark_bytes();
+        bytes[259] = 1;
+        assert!(CircuitGroth16Proof::from_gnark_bytes(&bytes).is_err());
+    }
+
+    /// Proves knowledge of `x` and `y` with `x * y = z` for a public `z`.
+    #[derive(Clone)]
+    struct MulCircuit {
+        x: Fr,
+        y: Fr,
+    }
+
+    impl ConstraintSynthesizer<Fr> for MulCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let x = cs.new_witness_variable(|| Ok(self.x))?;
+            let y = cs.new_witness_variable(|| Ok(self.y))?;
+            let z = cs.new_input_variable(|| Ok(self.x * self.y))?;
+            cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)
+        }
+    }
+
+    fn mul_proofs(
+        count: usize,
+    ) -> (
+        PreparedVerifyingKey<Bn254>,
+        Vec<(CircuitGroth16Proof, Vec<Fr>)>,
+    ) {
+        let mut rng = test_rng();
+        let circuit = MulCircuit {
+            x: Fr::zero(),
+            y: Fr::zero(),
+        };
+        let pk =
+            Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit, &mut rng).unwrap();
+        let pvk = ark_groth16::prepare_verifying_key(&pk.vk);
+
+        let proofs = (0..count)
+            .map(|_| {
+                let circuit = MulCircuit {
+                    x: Fr::rand(&mut rng),
+                    y: Fr::rand(&mut rng),
+                };
+                let z = circuit.x * circuit.y;
+                let proof =
+                    Groth16::<Bn254>::create_random_proof_with_reduction(circuit, &pk, &mut rng)
+                        .unwrap();
+                (CircuitGroth16Proof::new(proof.a, proof.b, proof.c), vec![z])
+            })
+            .collect();
+
+        (pvk, proofs)
+    }
+
+    #[test]
+    fn test_batch_verify() {
+        let (pvk, proofs) = mul_proofs(4);
+        for (proof, public_inputs) in &proofs {
+            assert!(Groth16::<Bn254>::verify_proof(&pvk, &(*proof).into(), public_inputs).unwrap());
+        }
+        assert!(batch_verify(&pvk, &proofs).unwrap());
+        assert!(batch_verify(&pvk, &proofs[..1]).unwrap());
+        assert!(!batch_verify(&pvk, &[]).unwrap());
+    }
+
+    #[test]
+    fn test_batch_verify_rejects_invalid_proof() {
+        let (pvk, mut proofs) = mul_proofs(3);
+        proofs[2].1[0] += Fr::one();
+        assert!(!batch_verify(&pvk, &proofs).unwrap());
+
+        let (pvk, mut proofs) = mul_proofs(3);
+        proofs.swap(0, 1);
+        proofs[0].1 = proofs[1].1.clone();
+        assert!(!batch_verify(&pvk, &proofs).unwrap());
+    }
+
+    #[test]
+    fn test_split_reversed_digest_matches_decimal_path() {
+        let mut rng = test_rng();
+        for _ in 0..16 {
+            let digest: [u8; 32] = rng.gen();
+            let digest_hex = hex::encode(digest);
+            let c0 = Fr::from_str(&to_decimal(&digest_hex[32..64]).unwrap()).unwrap();
+            let c1 = Fr::from_str(&to_decimal(&digest_hex[0..32]).unwrap()).unwrap();
+
+            assert_eq!(split_reversed_digest(&digest), (c0, c1));
+
+            let mut reversed = digest;
+            reversed.reverse();
+            assert_eq!(split_digest(&reversed), (c0, c1));
+        }
+    }
+
+    #[test]
+    fn test_control_root_and_id_to_fr() {
+        let control_root =
+            hex_literal::hex!("8cdad9242664be3112aba377c5425a4df735eb1c6966472b561d2855932c0469");
+        let control_id =
+            hex_literal::hex!("c07a65145c3cb48b6101962ea607a4dd93c753bb26975cb47feb00d3666e4404");
+
+        let (a0, a1) = control_root_to_fr(&control_root);
+        assert_eq!(
+            a0,
+            Fr::new(BigInt::new([
+                3584412468423285388,
+                5573840904707615506,
+                0,
+                0
+            ]))
+        );
+        assert_eq!(
+            a1,
+            Fr::new(BigInt::new([
+                3118573868620133879,
+                7567222285189782870,
+                0,
+                0
+            ]))
+        );
+        assert_eq!(
+            control_id_to_fr(&control_id),
+            Fr::new(BigInt::new([
+                10066737433256753856,
+                15970898588890169697,
+                12996428817291790227,
+                307492062473808767,
+            ]))
+        );
+    }
 }
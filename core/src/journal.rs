@@ -0,0 +1,310 @@
+use std::fmt;
+use std::io;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::winternitz::{WinternitzBatchCircuitOutput, WinternitzCircuitOutput};
+use crate::{ForkChoiceCircuitOutput, WorkOnlyCircuitOutput};
+
+/// First bytes of every versioned journal.
+pub const JOURNAL_MAGIC: [u8; 4] = *b"WNTZ";
+/// Size of the [`JournalHeader`] in bytes.
+pub const JOURNAL_HEADER_SIZE: usize = 6;
+
+/// Circuit that produced a journal. The header-chain journal is defined by the
+/// header-chain crate and verified recursively as is, so it is not enveloped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CircuitKind {
+    Winternitz = 0,
+    WinternitzBatch = 1,
+    WorkOnly = 2,
+    ForkChoice = 3,
+}
+
+impl TryFrom<u8> for CircuitKind {
+    type Error = JournalError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CircuitKind::Winternitz),
+            1 => Ok(CircuitKind::WinternitzBatch),
+            2 => Ok(CircuitKind::WorkOnly),
+            3 => Ok(CircuitKind::ForkChoice),
+            _ => Err(JournalError::UnknownKind(value)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum JournalError {
+    BadMagic,
+    UnknownKind(u8),
+    UnsupportedVersion {
+        kind: CircuitKind,
+        version: u8,
+    },
+    UnexpectedKind {
+        expected: CircuitKind,
+        found: CircuitKind,
+    },
+    Decode(io::Error),
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalError::BadMagic => write!(f, "Journal does not start with the journal magic"),
+            JournalError::UnknownKind(kind) => write!(f, "Unknown circuit kind {}", kind),
+            JournalError::UnsupportedVersion { kind, version } => {
+                write!(f, "Unsupported {:?} journal version {}", kind, version)
+            }
+            JournalError::UnexpectedKind { expected, found } => {
+                write!(f, "Expected a {:?} journal, found {:?}", expected, found)
+            }
+            JournalError::Decode(e) => write!(f, "Failed to decode journal payload: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+/// A guest output with a fixed circuit kind and layout version. Bump `VERSION`
+/// whenever the Borsh layout of the type changes.
+pub trait VersionedOutput: BorshSerialize + BorshDeserialize {
+    const KIND: CircuitKind;
+    const VERSION: u8;
+}
+
+impl VersionedOutput for WinternitzCircuitOutput {
+    const KIND: CircuitKind = CircuitKind::Winternitz;
+    const VERSION: u8 = 1;
+}
+
+impl VersionedOutput for WinternitzBatchCircuitOutput {
+    const KIND: CircuitKind = CircuitKind::WinternitzBatch;
+    const VERSION: u8 = 1;
+}
+
+impl VersionedOutput for WorkOnlyCircuitOutput {
+    const KIND: CircuitKind = CircuitKind::WorkOnly;
+    const VERSION: u8 = 1;
+}
+
+impl VersionedOutput for ForkChoiceCircuitOutput {
+    const KIND: CircuitKind = CircuitKind::ForkChoice;
+    const VERSION: u8 = 1;
+}
+
+/// `magic || kind || version`, in front of every journal payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalHeader {
+    pub kind: CircuitKind,
+    pub version: u8,
+}
+
+impl JournalHeader {
+    pub fn of<T: VersionedOutput>() -> Self {
+        Self {
+            kind: T::KIND,
+            version: T::VERSION,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; JOURNAL_HEADER_SIZE] {
+        let mut bytes = [0u8; JOURNAL_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&JOURNAL_MAGIC);
+        bytes[4] = self.kind as u8;
+        bytes[5] = self.version;
+        bytes
+    }
+
+    /// Reads the header at the start of `journal`. The version is not checked.
+    pub fn from_bytes(journal: &[u8]) -> Result<Self, JournalError> {
+        if journal.len() < JOURNAL_HEADER_SIZE || journal[0..4] != JOURNAL_MAGIC {
+            return Err(JournalError::BadMagic);
+        }
+        Ok(Self {
+            kind: CircuitKind::try_from(journal[4])?,
+            version: journal[5],
+        })
+    }
+}
+
+/// Wraps an output so that committing it writes the versioned journal.
+pub struct Journal<'a, T: VersionedOutput>(pub &'a T);
+
+impl<T: VersionedOutput> BorshSerialize for Journal<'_, T> {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&JournalHeader::of::<T>().to_bytes())?;
+        self.0.serialize(writer)
+    }
+}
+
+pub fn encode_journal<T: VersionedOutput>(output: &T) -> Vec<u8> {
+    borsh::to_vec(&Journal(output)).expect("Serialization to vec is infallible")
+}
+
+/// Decodes a journal of type `T`, rejecting other circuit kinds and versions.
+pub fn decode_journal<T: VersionedOutput>(journal: &[u8]) -> Result<T, JournalError> {
+    let header = JournalHeader::from_bytes(journal)?;
+    if header.kind != T::KIND {
+        return Err(JournalError::UnexpectedKind {
+            expected: T::KIND,
+            found: header.kind,
+        });
+    }
+    if header.version != T::VERSION {
+        return Err(JournalError::UnsupportedVersion {
+            kind: header.kind,
+            version: header.version,
+        });
+    }
+    T::try_from_slice(&journal[JOURNAL_HEADER_SIZE..]).map_err(JournalError::Decode)
+}
+
+/// Any journal this crate knows how to read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitJournal {
+    Winternitz(WinternitzCircuitOutput),
+    WinternitzBatch(WinternitzBatchCircuitOutput),
+    WorkOnly(WorkOnlyCircuitOutput),
+    ForkChoice(ForkChoiceCircuitOutput),
+}
+
+/// Decodes a journal of any circuit kind. Only the current version of each kind
+/// is accepted: a guest and the code reading its journals are upgraded together.
+/// Journals from before the envelope are read by [`decode_legacy_journal`].
+pub fn parse_journal(journal: &[u8]) -> Result<CircuitJournal, JournalError> {
+    match JournalHeader::from_bytes(journal)?.kind {
+        CircuitKind::Winternitz => decode_journal(journal).map(CircuitJournal::Winternitz),
+        CircuitKind::WinternitzBatch => {
+            decode_journal(journal).map(CircuitJournal::WinternitzBatch)
+        }
+        CircuitKind::WorkOnly => decode_journal(journal).map(CircuitJournal::WorkOnly),
+        CircuitKind::ForkChoice => decode_journal(journal).map(CircuitJournal::ForkChoice),
+    }
+}
+
+/// Output of a guest deployed before journals were versioned, committed as bare
+/// Borsh without a [`JournalHeader`]. These lack most fields of the current
+/// outputs, so they are decoded as they are instead of migrated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LegacyJournal {
+    WorkOnly { work_u128: [u32; 4] },
+    Winternitz { winternitz_pubkeys_digest: [u8; 20] },
+}
+
+/// Decodes a bare journal of `kind`. Batch and fork-choice journals were always
+/// versioned, so they fail with [`JournalError::UnsupportedVersion`] for version 0.
+pub fn decode_legacy_journal(
+    kind: CircuitKind,
+    journal: &[u8],
+) -> Result<LegacyJournal, JournalError> {
+    match kind {
+        CircuitKind::WorkOnly => <[u32; 4]>::try_from_slice(journal)
+            .map(|work_u128| LegacyJournal::WorkOnly { work_u128 }),
+        CircuitKind::Winternitz => {
+            <[u8; 20]>::try_from_slice(journal).map(|winternitz_pubkeys_digest| {
+                LegacyJournal::Winternitz {
+                    winternitz_pubkeys_digest,
+                }
+            })
+        }
+        CircuitKind::WinternitzBatch | CircuitKind::ForkChoice => {
+            return Err(JournalError::UnsupportedVersion { kind, version: 0 })
+        }
+    }
+    .map_err(JournalError::Decode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work_only_output() -> WorkOnlyCircuitOutput {
+        WorkOnlyCircuitOutput {
+            work_u128: [1, 2, 3, 4],
+            best_block_hash: [6; 32],
+            block_height: 7,
+            header_chain_method_id: [5; 8],
+            network_magic: [8; 4],
+            policy_digest: [9; 32],
+        }
+    }
+
+    #[test]
+    fn test_encode_and_decode_journal() {
+        let output = work_only_output();
+        let journal = encode_journal(&output);
+
+        assert_eq!(journal[0..4], JOURNAL_MAGIC);
+        assert_eq!(journal.len(), JOURNAL_HEADER_SIZE + 120);
+        assert_eq!(
+            decode_journal::<WorkOnlyCircuitOutput>(&journal).unwrap(),
+            output
+        );
+        assert_eq!(
+            parse_journal(&journal).unwrap(),
+            CircuitJournal::WorkOnly(output)
+        );
+    }
+
+    #[test]
+    fn test_decode_journal_rejects_other_kinds_and_versions() {
+        let mut journal = encode_journal(&work_only_output());
+
+        assert!(matches!(
+            decode_journal::<WinternitzCircuitOutput>(&journal),
+            Err(JournalError::UnexpectedKind { .. })
+        ));
+
+        journal[5] = WorkOnlyCircuitOutput::VERSION + 1;
+        assert!(matches!(
+            parse_journal(&journal),
+            Err(JournalError::UnsupportedVersion { .. })
+        ));
+
+        journal[4] = 0xff;
+        assert!(matches!(
+            parse_journal(&journal),
+            Err(JournalError::UnknownKind(0xff))
+        ));
+
+        assert!(matches!(
+            parse_journal(&borsh::to_vec(&work_only_output()).unwrap()),
+            Err(JournalError::BadMagic)
+        ));
+    }
+    #[test]
+    fn test_decode_legacy_journal() {
+        // Bare Borsh of the deployed `WorkOnlyCircuitOutput { work_u128 }`.
+        let legacy = borsh::to_vec(&[1u32, 2, 3, 4]).unwrap();
+        assert_eq!(
+            decode_legacy_journal(CircuitKind::WorkOnly, &legacy).unwrap(),
+            LegacyJournal::WorkOnly {
+                work_u128: [1, 2, 3, 4]
+            }
+        );
+        assert!(matches!(
+            parse_journal(&legacy),
+            Err(JournalError::BadMagic)
+        ));
+
+        let legacy = [7u8; 20];
+        assert_eq!(
+            decode_legacy_journal(CircuitKind::Winternitz, &legacy).unwrap(),
+            LegacyJournal::Winternitz {
+                winternitz_pubkeys_digest: [7; 20]
+            }
+        );
+        assert!(matches!(
+            decode_legacy_journal(CircuitKind::Winternitz, &legacy[1..]),
+            Err(JournalError::Decode(_))
+        ));
+        assert!(matches!(
+            decode_legacy_journal(CircuitKind::ForkChoice, &legacy),
+            Err(JournalError::UnsupportedVersion { version: 0, .. })
+        ));
+    }
+}
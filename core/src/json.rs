@@ -0,0 +1,670 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::groth16::{CircuitGroth16Proof, CompressedSealEncoding};
+use crate::policy::WorkOnlyPolicy;
+use crate::winternitz::{
+    Parameters, PublicKey, SecretKey, WinternitzBatchCircuitOutput, WinternitzCircuitInput,
+    WinternitzCircuitOutput,
+};
+use crate::{ForkChoiceCircuitOutput, WorkOnlyCircuitOutput};
+
+// JSON schema of the artifacts operators exchange outside Rust. Byte strings are
+// lower-case hex. Block hashes keep their internal byte order, not the reversed
+// order of RPC output. Image IDs are the digest bytes, little-endian words.
+// Bump the version when a field changes meaning or is removed.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JsonError {
+    UnsupportedVersion(u32),
+    InvalidHex(&'static str),
+    InvalidLength {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    InvalidParameters,
+    InvalidSeal,
+    /// A batch output has a different number of pubkey and policy digests.
+    BatchLengthMismatch {
+        pubkeys_digests: usize,
+        policy_digests: usize,
+    },
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported schema version {}, expected {}",
+                version, JSON_SCHEMA_VERSION
+            ),
+            JsonError::InvalidHex(field) => write!(f, "{} is not a hex string", field),
+            JsonError::InvalidLength {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} is {} bytes long, expected {}",
+                field, actual, expected
+            ),
+            JsonError::InvalidParameters => write!(f, "log_d must be between 4 and 8"),
+            JsonError::InvalidSeal => write!(f, "Seal is not a valid Groth16 proof"),
+            JsonError::BatchLengthMismatch {
+                pubkeys_digests,
+                policy_digests,
+            } => write!(
+                f,
+                "Batch output has {} pubkey digests but {} policy digests",
+                pubkeys_digests, policy_digests
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+fn from_hex_vec(field: &'static str, hex: &str) -> Result<Vec<u8>, JsonError> {
+    hex::decode(hex).map_err(|_| JsonError::InvalidHex(field))
+}
+
+fn from_hex<const N: usize>(field: &'static str, hex: &str) -> Result<[u8; N], JsonError> {
+    let bytes = from_hex_vec(field, hex)?;
+    let actual = bytes.len();
+    bytes.try_into().map_err(|_| JsonError::InvalidLength {
+        field,
+        expected: N,
+        actual,
+    })
+}
+
+fn image_id_to_hex(image_id: &[u32; 8]) -> String {
+    hex::encode(image_id.map(u32::to_le_bytes).concat())
+}
+
+fn image_id_from_hex(field: &'static str, hex: &str) -> Result<[u32; 8], JsonError> {
+    let bytes: [u8; 32] = from_hex(field, hex)?;
+    Ok(std::array::from_fn(|i| {
+        u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap())
+    }))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ParametersJson {
+    /// Number of message bytes signed.
+    pub n0: u32,
+    pub log_d: u32,
+}
+
+impl From<&Parameters> for ParametersJson {
+    fn from(params: &Parameters) -> Self {
+        Self {
+            n0: params.n0(),
+            log_d: params.log_d(),
+        }
+    }
+}
+
+impl TryFrom<&ParametersJson> for Parameters {
+    type Error = JsonError;
+
+    fn try_from(params: &ParametersJson) -> Result<Self, JsonError> {
+        if !(4..=8).contains(&params.log_d) {
+            return Err(JsonError::InvalidParameters);
+        }
+        Ok(Parameters::new(params.n0, params.log_d))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzPublicKeyJson {
+    pub params: ParametersJson,
+    /// hash160 of every digit chain's last element.
+    pub pub_key: Vec<String>,
+}
+
+impl WinternitzPublicKeyJson {
+    pub fn new(params: &Parameters, pub_key: &PublicKey) -> Self {
+        Self {
+            params: params.into(),
+            pub_key: pub_key.iter().map(hex::encode).collect(),
+        }
+    }
+
+    pub fn decode(&self) -> Result<(Parameters, PublicKey), JsonError> {
+        let pub_key = self
+            .pub_key
+            .iter()
+            .map(|hash| from_hex("pub_key", hash))
+            .collect::<Result<_, _>>()?;
+        Ok(((&self.params).try_into()?, pub_key))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzSecretKeyJson {
+    pub params: ParametersJson,
+    pub secret_key: String,
+}
+
+impl WinternitzSecretKeyJson {
+    pub fn new(params: &Parameters, secret_key: &SecretKey) -> Self {
+        Self {
+            params: params.into(),
+            secret_key: hex::encode(secret_key),
+        }
+    }
+
+    pub fn decode(&self) -> Result<(Parameters, SecretKey), JsonError> {
+        Ok((
+            (&self.params).try_into()?,
+            from_hex_vec("secret_key", &self.secret_key)?,
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzCircuitInputJson {
+    pub params: ParametersJson,
+    pub pub_key: Vec<String>,
+    pub signature: Vec<String>,
+    /// Compressed seal followed by the signed chain state.
+    pub message: String,
+    pub control_root: String,
+    pub seal_encoding: CompressedSealEncoding,
+}
+
+impl From<&WinternitzCircuitInput> for WinternitzCircuitInputJson {
+    fn from(input: &WinternitzCircuitInput) -> Self {
+        Self {
+            params: (&input.params).into(),
+            pub_key: input.pub_key.iter().map(hex::encode).collect(),
+            signature: input.signature.iter().map(hex::encode).collect(),
+            message: hex::encode(&input.message),
+            control_root: hex::encode(input.control_root),
+            seal_encoding: input.seal_encoding,
+        }
+    }
+}
+
+impl TryFrom<&WinternitzCircuitInputJson> for WinternitzCircuitInput {
+    type Error = JsonError;
+
+    fn try_from(input: &WinternitzCircuitInputJson) -> Result<Self, JsonError> {
+        Ok(WinternitzCircuitInput {
+            pub_key: input
+                .pub_key
+                .iter()
+                .map(|hash| from_hex("pub_key", hash))
+                .collect::<Result<_, _>>()?,
+            params: (&input.params).try_into()?,
+            signature: input
+                .signature
+                .iter()
+                .map(|digit| from_hex_vec("signature", digit))
+                .collect::<Result<_, _>>()?,
+            message: from_hex_vec("message", &input.message)?,
+            control_root: from_hex("control_root", &input.control_root)?,
+            seal_encoding: input.seal_encoding,
+        })
+    }
+}
+
+/// Groth16 proof as the 256-byte RISC Zero seal `a || b || c`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Groth16ProofJson {
+    pub seal: String,
+}
+
+impl From<&CircuitGroth16Proof> for Groth16ProofJson {
+    fn from(proof: &CircuitGroth16Proof) -> Self {
+        Self {
+            seal: hex::encode(proof.to_seal()),
+        }
+    }
+}
+
+impl TryFrom<&Groth16ProofJson> for CircuitGroth16Proof {
+    type Error = JsonError;
+
+    fn try_from(proof: &Groth16ProofJson) -> Result<Self, JsonError> {
+        CircuitGroth16Proof::from_seal_checked(&from_hex("seal", &proof.seal)?)
+            .map_err(|_| JsonError::InvalidSeal)
+    }
+}
+
+/// 128-byte compressed seal, as signed with Winternitz.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CompressedSealJson {
+    pub encoding: CompressedSealEncoding,
+    pub seal: String,
+}
+
+impl CompressedSealJson {
+    pub fn new(
+        proof: &CircuitGroth16Proof,
+        encoding: CompressedSealEncoding,
+    ) -> Result<Self, JsonError> {
+        let compressed = proof
+            .to_compressed_with(encoding)
+            .map_err(|_| JsonError::InvalidSeal)?;
+        Ok(Self {
+            encoding,
+            seal: hex::encode(compressed),
+        })
+    }
+
+    pub fn decode(&self) -> Result<CircuitGroth16Proof, JsonError> {
+        CircuitGroth16Proof::from_compressed_with(&from_hex("seal", &self.seal)?, self.encoding)
+            .map_err(|_| JsonError::InvalidSeal)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WorkOnlyPolicyJson {
+    pub min_total_work: String,
+    pub min_height: u32,
+    pub confirmed_block_hash: String,
+    pub min_confirmations: u32,
+}
+
+impl From<&WorkOnlyPolicy> for WorkOnlyPolicyJson {
+    fn from(policy: &WorkOnlyPolicy) -> Self {
+        Self {
+            min_total_work: hex::encode(policy.min_total_work),
+            min_height: policy.min_height,
+            confirmed_block_hash: hex::encode(policy.confirmed_block_hash),
+            min_confirmations: policy.min_confirmations,
+        }
+    }
+}
+
+impl TryFrom<&WorkOnlyPolicyJson> for WorkOnlyPolicy {
+    type Error = JsonError;
+
+    fn try_from(policy: &WorkOnlyPolicyJson) -> Result<Self, JsonError> {
+        Ok(WorkOnlyPolicy {
+            min_total_work: from_hex("min_total_work", &policy.min_total_work)?,
+            min_height: policy.min_height,
+            confirmed_block_hash: from_hex("confirmed_block_hash", &policy.confirmed_block_hash)?,
+            min_confirmations: policy.min_confirmations,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WorkOnlyOutputJson {
+    /// Low 128 bits of the total work, big-endian.
+    pub total_work: String,
+    pub best_block_hash: String,
+    pub block_height: u32,
+    pub header_chain_method_id: String,
+    pub network_magic: String,
+    pub policy_digest: String,
+}
+
+impl From<&WorkOnlyCircuitOutput> for WorkOnlyOutputJson {
+    fn from(output: &WorkOnlyCircuitOutput) -> Self {
+        Self {
+            total_work: hex::encode(output.work_u128.map(u32::to_be_bytes).concat()),
+            best_block_hash: hex::encode(output.best_block_hash),
+            block_height: output.block_height,
+            header_chain_method_id: image_id_to_hex(&output.header_chain_method_id),
+            network_magic: hex::encode(output.network_magic),
+            policy_digest: hex::encode(output.policy_digest),
+        }
+    }
+}
+
+impl TryFrom<&WorkOnlyOutputJson> for WorkOnlyCircuitOutput {
+    type Error = JsonError;
+
+    fn try_from(output: &WorkOnlyOutputJson) -> Result<Self, JsonError> {
+        let total_work: [u8; 16] = from_hex("total_work", &output.total_work)?;
+        Ok(WorkOnlyCircuitOutput {
+            work_u128: std::array::from_fn(|i| {
+                u32::from_be_bytes(total_work[i * 4..i * 4 + 4].try_into().unwrap())
+            }),
+            best_block_hash: from_hex("best_block_hash", &output.best_block_hash)?,
+            block_height: output.block_height,
+            header_chain_method_id: image_id_from_hex(
+                "header_chain_method_id",
+                &output.header_chain_method_id,
+            )?,
+            network_magic: from_hex("network_magic", &output.network_magic)?,
+            policy_digest: from_hex("policy_digest", &output.policy_digest)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ForkChoiceOutputJson {
+    pub heavier_best_block_hash: String,
+    pub lighter_best_block_hash: String,
+    pub work_difference: String,
+    pub genesis_state_hash: String,
+    pub header_chain_method_id: String,
+    pub network_magic: String,
+}
+
+impl From<&ForkChoiceCircuitOutput> for ForkChoiceOutputJson {
+    fn from(output: &ForkChoiceCircuitOutput) -> Self {
+        Self {
+            heavier_best_block_hash: hex::encode(output.heavier_best_block_hash),
+            lighter_best_block_hash: hex::encode(output.lighter_best_block_hash),
+            work_difference: hex::encode(output.work_difference),
+            genesis_state_hash: hex::encode(output.genesis_state_hash),
+            header_chain_method_id: image_id_to_hex(&output.header_chain_method_id),
+            network_magic: hex::encode(output.network_magic),
+        }
+    }
+}
+
+impl TryFrom<&ForkChoiceOutputJson> for ForkChoiceCircuitOutput {
+    type Error = JsonError;
+
+    fn try_from(output: &ForkChoiceOutputJson) -> Result<Self, JsonError> {
+        Ok(ForkChoiceCircuitOutput {
+            heavier_best_block_hash: from_hex(
+                "heavier_best_block_hash",
+                &output.heavier_best_block_hash,
+            )?,
+            lighter_best_block_hash: from_hex(
+                "lighter_best_block_hash",
+                &output.lighter_best_block_hash,
+            )?,
+            work_difference: from_hex("work_difference", &output.work_difference)?,
+            genesis_state_hash: from_hex("genesis_state_hash", &output.genesis_state_hash)?,
+            header_chain_method_id: image_id_from_hex(
+                "header_chain_method_id",
+                &output.header_chain_method_id,
+            )?,
+            network_magic: from_hex("network_magic", &output.network_magic)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzOutputJson {
+    pub winternitz_pubkeys_digest: String,
+    pub policy_digest: String,
+}
+
+impl From<&WinternitzCircuitOutput> for WinternitzOutputJson {
+    fn from(output: &WinternitzCircuitOutput) -> Self {
+        Self {
+            winternitz_pubkeys_digest: hex::encode(output.winternitz_pubkeys_digest),
+            policy_digest: hex::encode(output.policy_digest),
+        }
+    }
+}
+
+impl TryFrom<&WinternitzOutputJson> for WinternitzCircuitOutput {
+    type Error = JsonError;
+
+    fn try_from(output: &WinternitzOutputJson) -> Result<Self, JsonError> {
+        Ok(WinternitzCircuitOutput {
+            winternitz_pubkeys_digest: from_hex(
+                "winternitz_pubkeys_digest",
+                &output.winternitz_pubkeys_digest,
+            )?,
+            policy_digest: from_hex("policy_digest", &output.policy_digest)?,
+        })
+    }
+}
+
+/// Output of a batch run, one entry per input in order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WinternitzBatchOutputJson {
+    pub outputs: Vec<WinternitzOutputJson>,
+}
+
+impl TryFrom<&WinternitzBatchCircuitOutput> for WinternitzBatchOutputJson {
+    type Error = JsonError;
+
+    fn try_from(output: &WinternitzBatchCircuitOutput) -> Result<Self, JsonError> {
+        if output.winternitz_pubkeys_digests.len() != output.policy_digests.len() {
+            return Err(JsonError::BatchLengthMismatch {
+                pubkeys_digests: output.winternitz_pubkeys_digests.len(),
+                policy_digests: output.policy_digests.len(),
+            });
+        }
+        Ok(Self {
+            outputs: output
+                .winternitz_pubkeys_digests
+                .iter()
+                .zip(&output.policy_digests)
+                .map(|(pubkeys_digest, policy_digest)| WinternitzOutputJson {
+                    winternitz_pubkeys_digest: hex::encode(pubkeys_digest),
+                    policy_digest: hex::encode(policy_digest),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<&WinternitzBatchOutputJson> for WinternitzBatchCircuitOutput {
+    type Error = JsonError;
+
+    fn try_from(output: &WinternitzBatchOutputJson) -> Result<Self, JsonError> {
+        let outputs = output
+            .outputs
+            .iter()
+            .map(WinternitzCircuitOutput::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(WinternitzBatchCircuitOutput {
+            winternitz_pubkeys_digests: outputs
+                .iter()
+                .map(|output| output.winternitz_pubkeys_digest)
+                .collect(),
+            policy_digests: outputs.iter().map(|output| output.policy_digest).collect(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Artifact {
+    WinternitzPublicKey(WinternitzPublicKeyJson),
+    WinternitzSecretKey(WinternitzSecretKeyJson),
+    WinternitzCircuitInput(WinternitzCircuitInputJson),
+    Groth16Proof(Groth16ProofJson),
+    CompressedSeal(CompressedSealJson),
+    WorkOnlyPolicy(WorkOnlyPolicyJson),
+    WorkOnlyOutput(WorkOnlyOutputJson),
+    ForkChoiceOutput(ForkChoiceOutputJson),
+    WinternitzOutput(WinternitzOutputJson),
+    WinternitzBatchOutput(WinternitzBatchOutputJson),
+}
+
+/// Top-level JSON document: `{"version": 1, "type": "...", ...fields}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ArtifactJson {
+    pub version: u32,
+    #[serde(flatten)]
+    pub artifact: Artifact,
+}
+
+impl ArtifactJson {
+    pub fn new(artifact: Artifact) -> Self {
+        Self {
+            version: JSON_SCHEMA_VERSION,
+            artifact,
+        }
+    }
+
+    pub fn into_artifact(self) -> Result<Artifact, JsonError> {
+        if self.version != JSON_SCHEMA_VERSION {
+            return Err(JsonError::UnsupportedVersion(self.version));
+        }
+        Ok(self.artifact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::winternitz::{generate_public_key, sign_digits};
+    use ark_std::UniformRand;
+
+    fn round_trip(artifact: Artifact) -> Artifact {
+        let json = serde_json::to_string(&ArtifactJson::new(artifact)).unwrap();
+        serde_json::from_str::<ArtifactJson>(&json)
+            .unwrap()
+            .into_artifact()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_winternitz_input_round_trip() {
+        let params = Parameters::new(4, 8);
+        let secret_key = vec![1, 2, 3];
+        let message = vec![4, 5, 6, 7];
+        let input = WinternitzCircuitInput {
+            pub_key: generate_public_key(&params, &secret_key),
+            signature: sign_digits(&params, &secret_key, &message),
+            params: params.clone(),
+            message,
+            control_root: [8; 32],
+            seal_encoding: CompressedSealEncoding::BitVm,
+        };
+
+        let Artifact::WinternitzCircuitInput(json) =
+            round_trip(Artifact::WinternitzCircuitInput((&input).into()))
+        else {
+            panic!("Unexpected artifact type");
+        };
+        assert_eq!(WinternitzCircuitInput::try_from(&json).unwrap(), input);
+
+        let key = WinternitzPublicKeyJson::new(&params, &input.pub_key);
+        assert_eq!(key.decode().unwrap(), (params, input.pub_key));
+    }
+
+    #[test]
+    fn test_work_only_output_round_trip() {
+        let output = WorkOnlyCircuitOutput {
+            work_u128: [1, 2, 3, 0x04050607],
+            best_block_hash: [8; 32],
+            block_height: 9,
+            header_chain_method_id: [10, 11, 12, 13, 14, 15, 16, 17],
+            network_magic: [0xfa, 0xbf, 0xb5, 0xda],
+            policy_digest: WorkOnlyPolicy {
+                min_height: 18,
+                ..Default::default()
+            }
+            .digest(),
+        };
+        let json = WorkOnlyOutputJson::from(&output);
+        assert_eq!(json.total_work, "00000001000000020000000304050607");
+        assert_eq!(&json.header_chain_method_id[..16], "0a0000000b000000");
+        assert_eq!(WorkOnlyCircuitOutput::try_from(&json).unwrap(), output);
+    }
+
+    #[test]
+    fn test_winternitz_output_round_trip() {
+        let output = WinternitzCircuitOutput {
+            winternitz_pubkeys_digest: [1; 20],
+            policy_digest: [2; 32],
+        };
+        let Artifact::WinternitzOutput(json) =
+            round_trip(Artifact::WinternitzOutput((&output).into()))
+        else {
+            panic!("Unexpected artifact type");
+        };
+        assert_eq!(WinternitzCircuitOutput::try_from(&json).unwrap(), output);
+
+        let output = WinternitzBatchCircuitOutput {
+            winternitz_pubkeys_digests: vec![[3; 20], [4; 20]],
+            policy_digests: vec![[5; 32], [6; 32]],
+        };
+        let Artifact::WinternitzBatchOutput(json) = round_trip(Artifact::WinternitzBatchOutput(
+            (&output).try_into().unwrap(),
+        )) else {
+            panic!("Unexpected artifact type");
+        };
+        assert_eq!(json.outputs[1].policy_digest, hex::encode([6; 32]));
+        assert_eq!(
+            WinternitzBatchCircuitOutput::try_from(&json).unwrap(),
+            output
+        );
+
+        let output = WinternitzBatchCircuitOutput {
+            winternitz_pubkeys_digests: vec![[3; 20], [4; 20]],
+            policy_digests: vec![[5; 32]],
+        };
+        assert_eq!(
+            WinternitzBatchOutputJson::try_from(&output),
+            Err(JsonError::BatchLengthMismatch {
+                pubkeys_digests: 2,
+                policy_digests: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_work_only_policy_round_trip() {
+        let policy = WorkOnlyPolicy {
+            min_total_work: [1; 32],
+            min_height: 2,
+            confirmed_block_hash: [3; 32],
+            min_confirmations: 4,
+        };
+        let Artifact::WorkOnlyPolicy(json) = round_trip(Artifact::WorkOnlyPolicy((&policy).into()))
+        else {
+            panic!("Unexpected artifact type");
+        };
+        assert_eq!(WorkOnlyPolicy::try_from(&json).unwrap(), policy);
+    }
+
+    #[test]
+    fn test_seal_round_trip() {
+        let mut rng = ark_std::test_rng();
+        let proof = CircuitGroth16Proof::new(
+            ark_bn254::G1Affine::rand(&mut rng),
+            ark_bn254::G2Affine::rand(&mut rng),
+            ark_bn254::G1Affine::rand(&mut rng),
+        );
+
+        let json = Groth16ProofJson::from(&proof);
+        assert_eq!(
+            CircuitGroth16Proof::try_from(&json).unwrap().to_seal(),
+            proof.to_seal()
+        );
+        let compressed = CompressedSealJson::new(&proof, CompressedSealEncoding::Ark).unwrap();
+        assert_eq!(compressed.decode().unwrap().to_seal(), proof.to_seal());
+    }
+
+    #[test]
+    fn test_rejects_bad_documents() {
+        let json = r#"{"version": 2, "type": "groth16_proof", "seal": ""}"#;
+        let document: ArtifactJson = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            document.into_artifact(),
+            Err(JsonError::UnsupportedVersion(2))
+        );
+
+        let policy = WorkOnlyPolicyJson {
+            min_total_work: "00".to_string(),
+            min_height: 0,
+            confirmed_block_hash: "zz".to_string(),
+            min_confirmations: 0,
+        };
+        assert_eq!(
+            WorkOnlyPolicy::try_from(&policy),
+            Err(JsonError::InvalidLength {
+                field: "min_total_work",
+                expected: 32,
+                actual: 1
+            })
+        );
+
+        let params = ParametersJson { n0: 4, log_d: 9 };
+        assert_eq!(
+            Parameters::try_from(&params),
+            Err(JsonError::InvalidParameters)
+        );
+    }
+}
@@ -1,8 +1,19 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use header_chain::header_chain::BlockHeaderCircuitOutput;
+use header_chain::header_chain::{BlockHeaderCircuitOutput, CircuitBlockHeader};
+use policy::WorkOnlyPolicy;
 use serde::{Deserialize, Serialize};
 
+pub mod claim;
+pub mod constants;
+#[cfg(feature = "dev-mode")]
+pub mod dev;
 pub mod groth16;
+pub mod journal;
+pub mod json;
+pub mod message;
+pub mod mock;
+pub mod policy;
+pub mod registry;
 pub mod utils;
 pub mod winternitz;
 pub mod zkvm;
@@ -11,9 +22,39 @@ pub mod zkvm;
 pub struct WorkOnlyCircuitInput {
     pub header_chain_circuit_output: BlockHeaderCircuitOutput,
     pub method_id: [u32; 8],
+    pub policy: WorkOnlyPolicy,
+    /// Headers on top of `policy.confirmed_block_hash`, oldest first, up to the
+    /// best block. Only needed when the policy requires confirmations.
+    pub confirmation_headers: Vec<CircuitBlockHeader>,
 }
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug, BorshDeserialize, BorshSerialize)]
 pub struct WorkOnlyCircuitOutput {
     pub work_u128: [u32; 4],
+    pub best_block_hash: [u8; 32],
+    pub block_height: u32,
+    /// Image ID of the header-chain circuit whose output the work was taken from.
+    pub header_chain_method_id: [u32; 8],
+    /// Magic of the network the header chain was proven on.
+    pub network_magic: [u8; 4],
+    /// [`WorkOnlyPolicy::digest`] of the policy the chain was checked against.
+    pub policy_digest: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct ForkChoiceCircuitInput {
+    pub first: BlockHeaderCircuitOutput,
+    pub second: BlockHeaderCircuitOutput,
+    pub method_id: [u32; 8],
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct ForkChoiceCircuitOutput {
+    pub heavier_best_block_hash: [u8; 32],
+    pub lighter_best_block_hash: [u8; 32],
+    /// Total work of the heavier chain minus that of the lighter one, big-endian.
+    pub work_difference: [u8; 32],
+    pub genesis_state_hash: [u8; 32],
+    pub header_chain_method_id: [u32; 8],
+    pub network_magic: [u8; 4],
 }
@@ -0,0 +1,123 @@
+use ark_serialize::SerializationError;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::groth16::{CircuitGroth16Proof, CompressedSealEncoding};
+use crate::WorkOnlyCircuitOutput;
+
+pub const COMPRESSED_SEAL_SIZE: usize = 128;
+pub const TOTAL_WORK_SIZE: usize = 16;
+pub const BEST_BLOCK_HASH_SIZE: usize = 32;
+pub const BLOCK_HEIGHT_SIZE: usize = 4;
+pub const POLICY_DIGEST_SIZE: usize = 32;
+/// Size of the Borsh encoded [`SignedChainState`].
+pub const CHAIN_STATE_SIZE: usize =
+    TOTAL_WORK_SIZE + BEST_BLOCK_HASH_SIZE + BLOCK_HEIGHT_SIZE + POLICY_DIGEST_SIZE;
+/// Size of the Winternitz-signed message: compressed seal followed by the chain state.
+pub const SIGNED_MESSAGE_SIZE: usize = COMPRESSED_SEAL_SIZE + CHAIN_STATE_SIZE;
+
+/// The per-proof fields of the work-only output, carried in the signed message.
+/// The header-chain method ID and network are pinned in the winternitz guest
+/// instead, which keeps the message compact.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct SignedChainState {
+    pub total_work: [u8; TOTAL_WORK_SIZE],
+    pub best_block_hash: [u8; BEST_BLOCK_HASH_SIZE],
+    pub block_height: u32,
+    /// Digest of the policy the work-only guest enforced, so the signer commits
+    /// to it too.
+    pub policy_digest: [u8; POLICY_DIGEST_SIZE],
+}
+
+impl From<&WorkOnlyCircuitOutput> for SignedChainState {
+    fn from(output: &WorkOnlyCircuitOutput) -> Self {
+        Self {
+            total_work: borsh::to_vec(&output.work_u128)
+                .expect("Serialization to vec is infallible")
+                .try_into()
+                .unwrap(),
+            best_block_hash: output.best_block_hash,
+            block_height: output.block_height,
+            policy_digest: output.policy_digest,
+        }
+    }
+}
+
+/// Builds the message the operator signs, compressing the seal with `encoding`.
+pub fn encode_signed_message(
+    proof: &CircuitGroth16Proof,
+    chain_state: &SignedChainState,
+    encoding: CompressedSealEncoding,
+) -> Result<Vec<u8>, SerializationError> {
+    let compressed_seal = proof.to_compressed_with(encoding)?;
+    let chain_state = borsh::to_vec(chain_state)?;
+    Ok([&compressed_seal[..], &chain_state[..]].concat())
+}
+
+/// Splits a signed message into the decompressed seal and the chain state.
+pub fn decode_signed_message(
+    message: &[u8],
+    encoding: CompressedSealEncoding,
+) -> Result<(CircuitGroth16Proof, SignedChainState), SerializationError> {
+    if message.len() != SIGNED_MESSAGE_SIZE {
+        return Err(SerializationError::InvalidData);
+    }
+    let compressed_seal: [u8; COMPRESSED_SEAL_SIZE] =
+        message[0..COMPRESSED_SEAL_SIZE].try_into().unwrap();
+    let chain_state = SignedChainState::try_from_slice(&message[COMPRESSED_SEAL_SIZE..])?;
+
+    let proof = CircuitGroth16Proof::from_compressed_with(&compressed_seal, encoding)?;
+    Ok((proof, chain_state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_encode_and_decode_signed_message() {
+        let mut rng = ark_std::test_rng();
+        let proof = CircuitGroth16Proof::new(
+            ark_bn254::G1Affine::rand(&mut rng),
+            ark_bn254::G2Affine::rand(&mut rng),
+            ark_bn254::G1Affine::rand(&mut rng),
+        );
+        let chain_state = SignedChainState {
+            total_work: [7u8; TOTAL_WORK_SIZE],
+            best_block_hash: [8u8; BEST_BLOCK_HASH_SIZE],
+            block_height: 9,
+            policy_digest: [10u8; POLICY_DIGEST_SIZE],
+        };
+
+        for encoding in [CompressedSealEncoding::Ark, CompressedSealEncoding::BitVm] {
+            let message = encode_signed_message(&proof, &chain_state, encoding).unwrap();
+            assert_eq!(message.len(), SIGNED_MESSAGE_SIZE);
+
+            let (decoded, decoded_chain_state) = decode_signed_message(&message, encoding).unwrap();
+            assert_eq!(decoded.to_seal(), proof.to_seal());
+            assert_eq!(decoded_chain_state, chain_state);
+
+            assert!(decode_signed_message(&message[1..], encoding).is_err());
+        }
+    }
+
+    #[test]
+    fn test_signed_chain_state_from_work_only_output() {
+        let output = WorkOnlyCircuitOutput {
+            work_u128: [1, 2, 3, 4],
+            best_block_hash: [5; 32],
+            block_height: 6,
+            header_chain_method_id: [7; 8],
+            network_magic: [8; 4],
+            policy_digest: [9; 32],
+        };
+        let chain_state = SignedChainState::from(&output);
+
+        assert_eq!(
+            borsh::from_slice::<[u32; 4]>(&chain_state.total_work).unwrap(),
+            output.work_u128
+        );
+        assert_eq!(borsh::to_vec(&chain_state).unwrap().len(), CHAIN_STATE_SIZE);
+    }
+}
@@ -0,0 +1,263 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use borsh::BorshDeserialize;
+use sha2::{Digest, Sha256};
+
+use crate::zkvm::{GuestExitCode, Proof, ZkvmError, ZkvmGuest, ZkvmHost};
+
+/// In-memory [`ZkvmGuest`] for running circuits natively, e.g. in unit tests.
+///
+/// Inputs are read from a Borsh buffer, commits are appended to an in-memory
+/// journal and `verify` only accepts the `(method_id, journal)` pairs it was given.
+#[derive(Debug, Default)]
+pub struct MockGuest {
+    input: Vec<u8>,
+    cursor: Cell<usize>,
+    journal: RefCell<Vec<u8>>,
+    assumptions: Vec<([u32; 8], Vec<u8>)>,
+    recorded_cycles: RefCell<Vec<(&'static str, u64)>>,
+}
+
+impl MockGuest {
+    pub fn new(input: Vec<u8>) -> Self {
+        Self {
+            input,
+            ..Default::default()
+        }
+    }
+
+    /// Adds an assumption that [`ZkvmGuest::verify`] will accept.
+    pub fn with_assumption(mut self, method_id: [u32; 8], journal: Vec<u8>) -> Self {
+        self.assumptions.push((method_id, journal));
+        self
+    }
+
+    /// Everything committed so far.
+    pub fn journal(&self) -> Vec<u8> {
+        self.journal.borrow().clone()
+    }
+
+    /// Profiling spans recorded so far, in the order they ended.
+    pub fn recorded_cycles(&self) -> Vec<(&'static str, u64)> {
+        self.recorded_cycles.borrow().clone()
+    }
+}
+
+impl ZkvmGuest for MockGuest {
+    fn try_read_from_host<T: borsh::BorshDeserialize>(&self) -> Result<T, ZkvmError> {
+        let mut reader = &self.input[self.cursor.get()..];
+        let value =
+            BorshDeserialize::deserialize_reader(&mut reader).map_err(ZkvmError::Deserialize)?;
+        self.cursor.set(self.input.len() - reader.len());
+        Ok(value)
+    }
+
+    fn try_commit<T: borsh::BorshSerialize>(&self, item: &T) -> Result<(), ZkvmError> {
+        let buf = borsh::to_vec(item).map_err(ZkvmError::Serialize)?;
+        self.journal.borrow_mut().extend_from_slice(&buf);
+        Ok(())
+    }
+
+    fn try_verify<T: borsh::BorshSerialize>(
+        &self,
+        method_id: [u32; 8],
+        journal: &T,
+    ) -> Result<(), ZkvmError> {
+        let journal = borsh::to_vec(journal).map_err(ZkvmError::Serialize)?;
+        if !self
+            .assumptions
+            .iter()
+            .any(|(id, expected)| *id == method_id && *expected == journal)
+        {
+            return Err(ZkvmError::Verify(format!(
+                "No assumption for method id {:?}",
+                method_id
+            )));
+        }
+        Ok(())
+    }
+
+    fn cycle_count(&self) -> u64 {
+        0
+    }
+
+    fn exit(&self, code: GuestExitCode) -> ! {
+        panic!("Guest exited with code {:?}", code)
+    }
+
+    fn record_cycles(&self, name: &'static str, cycles: u64) {
+        self.recorded_cycles.borrow_mut().push((name, cycles));
+    }
+}
+
+/// [`ZkvmHost`] that runs registered circuits natively through a [`MockGuest`].
+///
+/// Circuits are keyed by the bytes passed as `elf`, which do not need to be a
+/// real ELF. The method ID of a circuit is derived from those bytes.
+#[derive(Default)]
+pub struct MockHost {
+    circuits: HashMap<Vec<u8>, fn(&MockGuest)>,
+    input: RefCell<Vec<u8>>,
+    assumptions: RefCell<Vec<([u32; 8], Vec<u8>)>>,
+}
+
+impl MockHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_circuit(mut self, elf: &[u8], circuit: fn(&MockGuest)) -> Self {
+        self.circuits.insert(elf.to_vec(), circuit);
+        self
+    }
+
+    /// Mock method ID of the circuit registered under `elf`.
+    pub fn method_id(elf: &[u8]) -> [u32; 8] {
+        let digest: [u8; 32] = Sha256::digest(elf).into();
+        let mut method_id = [0u32; 8];
+        for (word, bytes) in method_id.iter_mut().zip(digest.chunks(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        method_id
+    }
+}
+
+impl ZkvmHost for MockHost {
+    fn write<T: borsh::BorshSerialize>(&self, value: &T) {
+        let buf = borsh::to_vec(value).expect("Serialization to vec is infallible");
+        self.input.borrow_mut().extend_from_slice(&buf);
+    }
+
+    fn add_assumption(&self, proof: Proof) {
+        self.assumptions
+            .borrow_mut()
+            .push((proof.method_id, proof.journal));
+    }
+
+    fn prove(&self, elf: &[u8]) -> Proof {
+        self.execute(elf).expect("Failed to execute")
+    }
+
+    /// A [`MockGuest`] that exits panics, so this only fails by panicking.
+    fn execute(&self, elf: &[u8]) -> Result<Proof, ZkvmError> {
+        let circuit = self
+            .circuits
+            .get(elf)
+            .expect("No circuit registered for ELF");
+
+        let guest = self.assumptions.borrow().iter().fold(
+            MockGuest::new(self.input.borrow().clone()),
+            |guest, (id, journal)| guest.with_assumption(*id, journal.clone()),
+        );
+        circuit(&guest);
+
+        Ok(Proof {
+            method_id: MockHost::method_id(elf),
+            journal: guest.journal(),
+            #[cfg(feature = "host")]
+            receipt: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOUBLER_ELF: &[u8] = b"doubler";
+    const SUM_ELF: &[u8] = b"sum";
+
+    fn doubler(guest: &MockGuest) {
+        let value: u32 = guest.read_from_host();
+        guest.commit(&(value * 2));
+    }
+
+    /// Sums two doubled values after checking both against doubler proofs.
+    fn sum(guest: &MockGuest) {
+        let (a, b): (u32, u32) = guest.read_from_host();
+        guest.verify(MockHost::method_id(DOUBLER_ELF), &a);
+        guest.verify(MockHost::method_id(DOUBLER_ELF), &b);
+        guest.commit(&(a + b));
+    }
+
+    fn host() -> MockHost {
+        MockHost::new()
+            .with_circuit(DOUBLER_ELF, doubler)
+            .with_circuit(SUM_ELF, sum)
+    }
+
+    fn doubled(value: u32) -> Proof {
+        let host = host();
+        host.write(&value);
+        host.prove(DOUBLER_ELF)
+    }
+
+    #[test]
+    fn test_mock_guest_reads_and_commits() {
+        let guest = MockGuest::new(borsh::to_vec(&(1u32, vec![2u8, 3])).unwrap());
+        let (a, b): (u32, Vec<u8>) = guest.read_from_host();
+        guest.commit(&a);
+        guest.commit(&b);
+
+        assert_eq!(
+            guest.journal(),
+            borsh::to_vec(&(1u32, vec![2u8, 3])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mock_host_with_assumptions() {
+        let (a, b) = (doubled(2), doubled(5));
+        assert_eq!(a.journal, borsh::to_vec(&4u32).unwrap());
+
+        let host = host();
+        host.add_assumption(a);
+        host.add_assumption(b);
+        host.write(&(4u32, 10u32));
+        let proof = host.prove(SUM_ELF);
+
+        assert_eq!(proof.method_id, MockHost::method_id(SUM_ELF));
+        assert_eq!(proof.journal, borsh::to_vec(&14u32).unwrap());
+    }
+
+    #[test]
+    fn test_mock_guest_try_apis() {
+        let guest = MockGuest::new(vec![1, 0]);
+        assert!(matches!(
+            guest.try_read_from_host::<u32>(),
+            Err(ZkvmError::Deserialize(_))
+        ));
+        assert_eq!(guest.try_read_from_host::<u16>().unwrap(), 1);
+        assert!(matches!(
+            guest.try_verify([0; 8], &1u32),
+            Err(ZkvmError::Verify(_))
+        ));
+        assert!(guest.try_commit(&1u8).is_ok());
+        assert_eq!(guest.journal(), vec![1]);
+    }
+
+    #[test]
+    fn test_mock_guest_records_spans() {
+        let guest = MockGuest::new(vec![]);
+        {
+            let _span = guest.span("outer");
+            let _inner = guest.span("inner");
+        }
+
+        let expected: Vec<(&str, u64)> = match cfg!(feature = "profiling") {
+            true => vec![("inner", 0), ("outer", 0)],
+            false => vec![],
+        };
+        assert_eq!(guest.recorded_cycles(), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "No assumption")]
+    fn test_mock_host_rejects_missing_assumption() {
+        let host = host();
+        host.add_assumption(doubled(2));
+        host.write(&(4u32, 11u32));
+        host.prove(SUM_ELF);
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const WORK_ONLY_POLICY_SIZE: usize = 72;
+
+/// Thresholds the work-only guest enforces before committing. The default policy
+/// accepts any chain.
+#[derive(
+    Serialize, Deserialize, Eq, PartialEq, Clone, Debug, Default, BorshDeserialize, BorshSerialize,
+)]
+pub struct WorkOnlyPolicy {
+    /// Minimum total work, big-endian like `ChainState::total_work`.
+    pub min_total_work: [u8; 32],
+    pub min_height: u32,
+    /// Block that must have at least `min_confirmations` blocks on top of it.
+    /// Ignored when `min_confirmations` is zero.
+    pub confirmed_block_hash: [u8; 32],
+    pub min_confirmations: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PolicyError {
+    InsufficientWork,
+    InsufficientHeight {
+        height: u32,
+        min_height: u32,
+    },
+    InsufficientConfirmations {
+        confirmations: u32,
+        min_confirmations: u32,
+    },
+    /// The confirmation headers do not link the confirmed block to the best block.
+    BrokenChain,
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyError::InsufficientWork => write!(f, "Total work is below the minimum"),
+            PolicyError::InsufficientHeight { height, min_height } => {
+                write!(f, "Height {} is below the minimum {}", height, min_height)
+            }
+            PolicyError::InsufficientConfirmations {
+                confirmations,
+                min_confirmations,
+            } => write!(
+                f,
+                "{} confirmations, at least {} required",
+                confirmations, min_confirmations
+            ),
+            PolicyError::BrokenChain => {
+                write!(f, "Confirmation headers do not lead to the best block")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl WorkOnlyPolicy {
+    /// SHA-256 of the Borsh encoded policy. Outputs and signed messages carry the
+    /// digest rather than the policy itself.
+    pub fn digest(&self) -> [u8; 32] {
+        Sha256::digest(borsh::to_vec(self).expect("Serialization to vec is infallible")).into()
+    }
+
+    /// Checks a verified chain state against the policy. `confirmation_headers`
+    /// are the `(prev_block_hash, block_hash)` pairs of the blocks on top of the
+    /// confirmed block, oldest first, ending with the best block.
+    pub fn check(
+        &self,
+        total_work: &[u8; 32],
+        block_height: u32,
+        best_block_hash: &[u8; 32],
+        confirmation_headers: &[([u8; 32], [u8; 32])],
+    ) -> Result<(), PolicyError> {
+        // Both are big-endian and of equal length, so they compare as numbers.
+        if total_work < &self.min_total_work {
+            return Err(PolicyError::InsufficientWork);
+        }
+        if block_height < self.min_height {
+            return Err(PolicyError::InsufficientHeight {
+                height: block_height,
+                min_height: self.min_height,
+            });
+        }
+        if self.min_confirmations == 0 {
+            return Ok(());
+        }
+
+        let mut tip = self.confirmed_block_hash;
+        for (prev_block_hash, block_hash) in confirmation_headers {
+            if *prev_block_hash != tip {
+                return Err(PolicyError::BrokenChain);
+            }
+            tip = *block_hash;
+        }
+        if tip != *best_block_hash {
+            return Err(PolicyError::BrokenChain);
+        }
+
+        let confirmations = confirmation_headers.len() as u32;
+        if confirmations < self.min_confirmations {
+            return Err(PolicyError::InsufficientConfirmations {
+                confirmations,
+                min_confirmations: self.min_confirmations,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(len: u8) -> Vec<([u8; 32], [u8; 32])> {
+        (0..len).map(|i| ([i; 32], [i + 1; 32])).collect()
+    }
+
+    #[test]
+    fn test_policy_thresholds() {
+        let mut total_work = [0u8; 32];
+        total_work[31] = 10;
+        let policy = WorkOnlyPolicy {
+            min_total_work: total_work,
+            min_height: 5,
+            ..Default::default()
+        };
+
+        assert_eq!(borsh::to_vec(&policy).unwrap().len(), WORK_ONLY_POLICY_SIZE);
+        assert!(WorkOnlyPolicy::default()
+            .check(&[0; 32], 0, &[0; 32], &[])
+            .is_ok());
+        assert!(policy.check(&total_work, 5, &[0; 32], &[]).is_ok());
+        assert_eq!(
+            policy.check(&[0; 32], 5, &[0; 32], &[]),
+            Err(PolicyError::InsufficientWork)
+        );
+        assert_eq!(
+            policy.check(&total_work, 4, &[0; 32], &[]),
+            Err(PolicyError::InsufficientHeight {
+                height: 4,
+                min_height: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_policy_confirmations() {
+        let policy = WorkOnlyPolicy {
+            confirmed_block_hash: [0; 32],
+            min_confirmations: 3,
+            ..Default::default()
+        };
+
+        assert!(policy.check(&[0; 32], 0, &[3; 32], &chain(3)).is_ok());
+        assert_eq!(
+            policy.check(&[0; 32], 0, &[2; 32], &chain(2)),
+            Err(PolicyError::InsufficientConfirmations {
+                confirmations: 2,
+                min_confirmations: 3
+            })
+        );
+        assert_eq!(
+            policy.check(&[0; 32], 0, &[4; 32], &chain(3)),
+            Err(PolicyError::BrokenChain)
+        );
+        assert_eq!(
+            policy.check(&[0; 32], 0, &[4; 32], &chain(4)[1..]),
+            Err(PolicyError::BrokenChain)
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::PreparedVerifyingKey;
+use ark_serialize::{CanonicalDeserialize, SerializationError};
+
+use crate::constants::{BITCOIN_NETWORK, NETWORK_MAGICS, RISC0_V1_2_PREPARED_VK};
+use crate::groth16::{control_id_to_fr, control_root_to_fr};
+
+/// A RISC Zero Groth16 verifier setup accepted by the winternitz guest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Risc0VerifierEntry {
+    pub control_root: [u8; 32],
+    pub bn254_control_id: [u8; 32],
+    pub prepared_vk: &'static [u8],
+}
+
+/// Allowed verifier setups, oldest first. Keep retired entries here until every
+/// proof made against them has been asserted, so a control root rotation can be
+/// rolled out without rejecting in-flight proofs.
+pub static RISC0_VERIFIER_REGISTRY: &[Risc0VerifierEntry] = &[
+    // risc0 v1.2
+    Risc0VerifierEntry {
+        control_root: hex_literal::hex!(
+            "8cdad9242664be3112aba377c5425a4df735eb1c6966472b561d2855932c0469"
+        ),
+        bn254_control_id: hex_literal::hex!(
+            "c07a65145c3cb48b6101962ea607a4dd93c753bb26975cb47feb00d3666e4404"
+        ),
+        prepared_vk: RISC0_V1_2_PREPARED_VK,
+    },
+];
+
+impl Risc0VerifierEntry {
+    /// Control root limbs (`a0`, `a1`) used as the first two public inputs.
+    pub fn control_root_fr(&self) -> (Fr, Fr) {
+        control_root_to_fr(&self.control_root)
+    }
+
+    /// BN254 control ID used as the last public input.
+    pub fn bn254_control_id_fr(&self) -> Fr {
+        control_id_to_fr(&self.bn254_control_id)
+    }
+
+    pub fn prepared_vk(&self) -> Result<PreparedVerifyingKey<Bn254>, SerializationError> {
+        PreparedVerifyingKey::deserialize_uncompressed(self.prepared_vk)
+    }
+}
+
+/// Returns the registry entry with the given control root, if it is allowed. With
+/// the `dev-mode` feature the test setup in [`crate::dev`] is allowed too.
+pub fn find_verifier_entry(control_root: &[u8; 32]) -> Option<&'static Risc0VerifierEntry> {
+    #[cfg(feature = "dev-mode")]
+    if control_root == &crate::dev::DEV_VERIFIER_ENTRY.control_root {
+        return Some(&crate::dev::DEV_VERIFIER_ENTRY);
+    }
+    RISC0_VERIFIER_REGISTRY
+        .iter()
+        .find(|entry| &entry.control_root == control_root)
+}
+
+/// Returns the most recently added registry entry, used when producing new inputs.
+pub fn latest_verifier_entry() -> &'static Risc0VerifierEntry {
+    RISC0_VERIFIER_REGISTRY
+        .last()
+        .expect("Verifier registry is not empty")
+}
+
+// Header-chain image IDs accepted by the work-only guest, generated by the build
+// script from `elfs/<network>-headerchain-guest`.
+include!(concat!(env!("OUT_DIR"), "/header_chain_method_ids.rs"));
+
+/// Returns the header-chain image ID accepted on `network`.
+pub fn header_chain_method_id(network: &str) -> Option<[u32; 8]> {
+    HEADER_CHAIN_METHOD_IDS
+        .iter()
+        .find(|(name, _)| *name == network)
+        .map(|(_, method_id)| *method_id)
+}
+
+/// Returns the header-chain image ID accepted on the network the crate was built for.
+pub fn pinned_header_chain_method_id() -> [u32; 8] {
+    header_chain_method_id(BITCOIN_NETWORK).expect("No header-chain image ID for the build network")
+}
+
+// Work-only image IDs, whose proofs the winternitz guest verifies, generated by
+// the build script from `elfs/<network>-work-only-guest`.
+include!(concat!(env!("OUT_DIR"), "/work_only_method_ids.rs"));
+
+/// Returns the work-only image ID of `network`.
+pub fn work_only_method_id(network: &str) -> Option<[u32; 8]> {
+    WORK_ONLY_METHOD_IDS
+        .iter()
+        .find(|(name, _)| *name == network)
+        .map(|(_, method_id)| *method_id)
+}
+
+/// Returns the work-only image ID of the network the crate was built for.
+pub fn pinned_work_only_method_id() -> [u32; 8] {
+    work_only_method_id(BITCOIN_NETWORK).expect("No work-only image ID for the build network")
+}
+
+pub fn network_magic(network: &str) -> Option<[u8; 4]> {
+    NETWORK_MAGICS
+        .iter()
+        .find(|(name, _)| *name == network)
+        .map(|(_, magic)| *magic)
+}
+
+/// Returns the magic of the network the crate was built for.
+pub fn pinned_network_magic() -> [u8; 4] {
+    network_magic(BITCOIN_NETWORK).expect("Unknown build network")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_entries_are_valid() {
+        for (i, entry) in RISC0_VERIFIER_REGISTRY.iter().enumerate() {
+            assert!(entry.prepared_vk().is_ok());
+            assert_eq!(
+                RISC0_VERIFIER_REGISTRY
+                    .iter()
+                    .position(|other| other.control_root == entry.control_root),
+                Some(i),
+                "Duplicate control root in registry"
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_verifier_entry() {
+        let latest = latest_verifier_entry();
+        assert_eq!(find_verifier_entry(&latest.control_root), Some(latest));
+        assert_eq!(find_verifier_entry(&[0u8; 32]), None);
+    }
+
+    #[test]
+    fn test_header_chain_method_ids() {
+        for (network, method_id) in HEADER_CHAIN_METHOD_IDS {
+            assert_eq!(header_chain_method_id(network), Some(*method_id));
+        }
+        assert_eq!(header_chain_method_id("unknown"), None);
+    }
+
+    #[test]
+    fn test_work_only_method_ids() {
+        for (network, method_id) in WORK_ONLY_METHOD_IDS {
+            assert_eq!(work_only_method_id(network), Some(*method_id));
+        }
+        assert_eq!(work_only_method_id("unknown"), None);
+    }
+
+    #[test]
+    fn test_network_magic() {
+        assert_eq!(network_magic("mainnet"), Some([0xf9, 0xbe, 0xb4, 0xd9]));
+        assert_eq!(network_magic("unknown"), None);
+        assert_eq!(network_magic(BITCOIN_NETWORK), Some(pinned_network_magic()));
+    }
+}
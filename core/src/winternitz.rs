@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 pub type HashOut = [u8; 20];
 pub type PublicKey = Vec<HashOut>;
 pub type SecretKey = Vec<u8>;
+use crate::groth16::CompressedSealEncoding;
 use crate::utils::hash160;
 use bitcoin::hashes::{self, Hash};
 
@@ -12,11 +13,39 @@ pub struct WinternitzCircuitInput {
     pub params: Parameters,
     pub signature: Vec<Vec<u8>>,
     pub message: Vec<u8>,
+    /// Control root of the RISC Zero verifier setup the signed seal was made for.
+    /// Must be present in [`crate::registry::RISC0_VERIFIER_REGISTRY`].
+    pub control_root: [u8; 32],
+    /// Encoding of the compressed seal at the start of `message`.
+    pub seal_encoding: CompressedSealEncoding,
+}
+
+/// Input read by the winternitz guest: one signed seal, or several seals whose
+/// Groth16 proofs are verified together.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub enum WinternitzGuestInput {
+    Single(WinternitzCircuitInput),
+    Batch(Vec<WinternitzCircuitInput>),
 }
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug, BorshDeserialize, BorshSerialize)]
 pub struct WinternitzCircuitOutput {
     pub winternitz_pubkeys_digest: [u8; 20],
+    /// Policy digest of the signed chain state, so verifiers can tell which
+    /// policy the proven chain met.
+    pub policy_digest: [u8; 32],
+}
+
+/// Output of a batch run, with the pubkeys and policy digests of every input in order.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct WinternitzBatchCircuitOutput {
+    pub winternitz_pubkeys_digests: Vec<[u8; 20]>,
+    pub policy_digests: Vec<[u8; 32]>,
+}
+
+/// hash160 of the concatenated public key hashes.
+pub fn pubkeys_digest(pub_key: &PublicKey) -> [u8; 20] {
+    hash160(&pub_key.concat())
 }
 
 pub fn verify_signature(input: &WinternitzCircuitInput) -> bool {
@@ -35,7 +64,6 @@ pub fn verify_signature(input: &WinternitzCircuitInput) -> bool {
             (0..(input.params.d - digit as u32)).fold(signature_byte_arr, |hash, _| hash160(&hash));
 
         if hash_bytes != input.pub_key[i] {
-            println!("{:?}, {:?}", hash_bytes, input.pub_key[i]);
             return false;
         }
     }
@@ -50,7 +78,6 @@ pub fn verify_signature(input: &WinternitzCircuitInput) -> bool {
             .fold(signature_byte_arr, |hash, _| hash160(&hash));
 
         if hash_bytes != pubkey {
-            println!("{:?}, {:?}", hash_bytes, pubkey);
             return false;
         }
     }
@@ -125,6 +152,15 @@ impl Parameters {
             n,
         }
     }
+
+    /// Number of message bytes signed.
+    pub fn n0(&self) -> u32 {
+        self.n0
+    }
+
+    pub fn log_d(&self) -> u32 {
+        self.log_d
+    }
 }
 
 fn public_key_for_digit(ps: &Parameters, secret_key: &SecretKey, digit_index: u32) -> HashOut {
@@ -250,11 +286,26 @@ mod tests {
             params: ps,
             signature: signature.clone(),
             message: message.clone(),
+            control_root: [0u8; 32],
+            seal_encoding: CompressedSealEncoding::Ark,
         };
 
         assert!(verify_signature(&input));
     }
 
+    #[test]
+    fn test_pubkeys_digest() {
+        let ps = Parameters::new(4, 4);
+        let public_key = generate_public_key(&ps, &vec![1, 2, 3, 4]);
+
+        let mut pub_key_concat: Vec<u8> = vec![0; public_key.len() * 20];
+        for (i, pubkey) in public_key.iter().enumerate() {
+            pub_key_concat[i * 20..(i + 1) * 20].copy_from_slice(pubkey);
+        }
+
+        assert_eq!(pubkeys_digest(&public_key), hash160(&pub_key_concat));
+    }
+
     #[test]
     fn test_invalid_signature() {
         let ps = Parameters::new(4, 4);
@@ -271,6 +322,8 @@ mod tests {
             params: ps,
             signature,
             message,
+            control_root: [0u8; 32],
+            seal_encoding: CompressedSealEncoding::Ark,
         };
 
         assert!(!verify_signature(&input));
@@ -1,18 +1,222 @@
+use std::fmt;
 use std::io::Write;
 
 use borsh::BorshDeserialize;
 use risc0_zkvm::guest::env::{self};
+#[cfg(feature = "host")]
+use risc0_zkvm::{
+    compute_image_id, default_executor, default_prover, ExecutorEnv, ExitCode, ProverOpts, Receipt,
+    SessionInfo, VerificationError,
+};
+#[cfg(all(feature = "host", feature = "dev-mode"))]
+use risc0_zkvm::{sha::Digestible, FakeReceipt, InnerReceipt, ReceiptClaim};
+#[cfg(feature = "host")]
+use std::cell::RefCell;
+
+#[derive(Debug)]
+pub enum ZkvmError {
+    /// The host input could not be decoded as the requested type.
+    Deserialize(std::io::Error),
+    /// A value could not be encoded for the journal.
+    Serialize(std::io::Error),
+    /// Writing to the journal failed.
+    Journal(std::io::Error),
+    /// An assumption could not be added for the given method ID and journal.
+    Verify(String),
+    /// The guest halted with a non-zero exit code.
+    Exited(u32),
+    /// The guest stopped without halting, e.g. it paused.
+    NotHalted(String),
+}
+
+impl fmt::Display for ZkvmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkvmError::Deserialize(e) => write!(f, "Failed to deserialize input from host: {}", e),
+            ZkvmError::Serialize(e) => write!(f, "Failed to serialize journal entry: {}", e),
+            ZkvmError::Journal(e) => write!(f, "Failed to write to journal: {}", e),
+            ZkvmError::Verify(e) => write!(f, "Failed to verify assumption: {}", e),
+            ZkvmError::Exited(code) => match GuestExitCode::from_code(*code) {
+                Some(exit_code) => write!(f, "Guest exited with {:?} ({})", exit_code, code),
+                None => write!(f, "Guest exited with code {}", code),
+            },
+            ZkvmError::NotHalted(exit_code) => write!(f, "Guest did not halt: {}", exit_code),
+        }
+    }
+}
+
+impl std::error::Error for ZkvmError {}
+
+/// Exit codes for guests that abort instead of committing an output. A receipt
+/// with a non-zero exit code does not verify, so aborting makes the run unprovable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GuestExitCode {
+    InvalidInput = 1,
+    CommitFailed = 2,
+    AssumptionFailed = 3,
+    /// A value does not fit the width it is committed with.
+    Overflow = 4,
+    /// The proven chain does not meet the requested policy.
+    PolicyViolated = 5,
+    /// A signature or proof in the input does not verify.
+    VerificationFailed = 6,
+}
+
+impl GuestExitCode {
+    pub const ALL: [GuestExitCode; 6] = [
+        GuestExitCode::InvalidInput,
+        GuestExitCode::CommitFailed,
+        GuestExitCode::AssumptionFailed,
+        GuestExitCode::Overflow,
+        GuestExitCode::PolicyViolated,
+        GuestExitCode::VerificationFailed,
+    ];
+
+    /// Returns the exit code a guest halted with as `code`, if it is one of ours.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|exit_code| *exit_code as u32 == code)
+    }
+}
 
 pub trait ZkvmGuest {
-    fn read_from_host<T: borsh::BorshDeserialize>(&self) -> T;
-    fn commit<T: borsh::BorshSerialize>(&self, item: &T);
-    fn verify<T: borsh::BorshSerialize>(&self, method_id: [u32; 8], journal: &T);
+    fn try_read_from_host<T: borsh::BorshDeserialize>(&self) -> Result<T, ZkvmError>;
+    fn try_commit<T: borsh::BorshSerialize>(&self, item: &T) -> Result<(), ZkvmError>;
+    fn try_verify<T: borsh::BorshSerialize>(
+        &self,
+        method_id: [u32; 8],
+        journal: &T,
+    ) -> Result<(), ZkvmError>;
+    fn cycle_count(&self) -> u64;
+    /// Halts the guest with `code`.
+    fn exit(&self, code: GuestExitCode) -> !;
+
+    /// Reports the cycles spent in a profiling span. Printed as a
+    /// [`CYCLES_MARKER`] line the host collects into a [`CycleReport`].
+    fn record_cycles(&self, name: &'static str, cycles: u64) {
+        println!("{}{} {}", CYCLES_MARKER, name, cycles);
+    }
+
+    /// Starts a profiling span that records its cycle count when dropped.
+    fn span(&self, name: &'static str) -> CycleSpan<'_, Self>
+    where
+        Self: Sized,
+    {
+        CycleSpan::new(self, name)
+    }
+
+    fn read_from_host<T: borsh::BorshDeserialize>(&self) -> T {
+        self.try_read_from_host()
+            .expect("Failed to deserialize input from host")
+    }
+
+    fn commit<T: borsh::BorshSerialize>(&self, item: &T) {
+        self.try_commit(item).expect("Failed to commit")
+    }
+
+    fn verify<T: borsh::BorshSerialize>(&self, method_id: [u32; 8], journal: &T) {
+        self.try_verify(method_id, journal)
+            .expect("Failed to verify assumption")
+    }
+}
+
+/// Prefix of the guest output lines carrying profiling spans.
+pub const CYCLES_MARKER: &str = "[cycles] ";
+
+/// Profiling span returned by [`ZkvmGuest::span`]. Without the `profiling`
+/// feature it neither reads the cycle counter nor reports anything, so production
+/// ELFs don't pay for it.
+pub struct CycleSpan<'a, G: ZkvmGuest> {
+    guest: &'a G,
+    name: &'static str,
+    start: u64,
+}
+
+impl<'a, G: ZkvmGuest> CycleSpan<'a, G> {
+    pub fn new(guest: &'a G, name: &'static str) -> Self {
+        let start = match cfg!(feature = "profiling") {
+            true => guest.cycle_count(),
+            false => 0,
+        };
+        Self { guest, name, start }
+    }
+}
+
+impl<G: ZkvmGuest> Drop for CycleSpan<'_, G> {
+    fn drop(&mut self) {
+        if cfg!(feature = "profiling") {
+            let cycles = self.guest.cycle_count() - self.start;
+            self.guest.record_cycles(self.name, cycles);
+        }
+    }
+}
+
+/// Per-stage cycle counts of a guest execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CycleReport {
+    /// Cycles per span name, summed over repeated spans, in first-seen order.
+    pub stages: Vec<(String, u64)>,
+    pub total_cycles: u64,
+    pub segments: usize,
+}
+
+impl CycleReport {
+    /// Collects the [`CYCLES_MARKER`] lines of the guest output.
+    pub fn from_guest_output(output: &str, total_cycles: u64, segments: usize) -> Self {
+        let mut stages: Vec<(String, u64)> = Vec::new();
+        for line in output.lines() {
+            let Some((name, cycles)) = line
+                .strip_prefix(CYCLES_MARKER)
+                .and_then(|record| record.rsplit_once(' '))
+            else {
+                continue;
+            };
+            let Ok(cycles) = cycles.parse::<u64>() else {
+                continue;
+            };
+            match stages.iter_mut().find(|(stage, _)| stage == name) {
+                Some((_, total)) => *total += cycles,
+                None => stages.push((name.to_string(), cycles)),
+            }
+        }
+
+        Self {
+            stages,
+            total_cycles,
+            segments,
+        }
+    }
+
+    pub fn stage(&self, name: &str) -> Option<u64> {
+        self.stages
+            .iter()
+            .find(|(stage, _)| stage == name)
+            .map(|(_, cycles)| *cycles)
+    }
+}
+
+impl fmt::Display for CycleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (stage, cycles) in &self.stages {
+            writeln!(f, "{}: {}", stage, cycles)?;
+        }
+        write!(
+            f,
+            "total: {} cycles in {} segments",
+            self.total_cycles, self.segments
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Proof {
     pub method_id: [u32; 8],
     pub journal: Vec<u8>,
+    /// Receipt backing the proof, `None` when the guest was only executed.
+    #[cfg(feature = "host")]
+    pub receipt: Option<Receipt>,
 }
 
 pub trait ZkvmHost {
@@ -22,9 +226,10 @@ pub trait ZkvmHost {
     fn add_assumption(&self, proof: Proof);
 
     // Proves with the given data
-    fn prove(&self, elf: &[u32]) -> Proof;
+    fn prove(&self, elf: &[u8]) -> Proof;
 
-    fn execute(&self, elf: &[u32]) -> Proof;
+    /// Runs the guest without proving. Fails when it does not halt with exit code 0.
+    fn execute(&self, elf: &[u8]) -> Result<Proof, ZkvmError>;
 }
 
 #[derive(Debug, Clone)]
@@ -37,20 +242,225 @@ impl Risc0Guest {
 }
 
 impl ZkvmGuest for Risc0Guest {
-    fn read_from_host<T: borsh::BorshDeserialize>(&self) -> T {
+    fn try_read_from_host<T: borsh::BorshDeserialize>(&self) -> Result<T, ZkvmError> {
         let mut reader = env::stdin();
-        BorshDeserialize::deserialize_reader(&mut reader)
-            .expect("Failed to deserialize input from host")
+        BorshDeserialize::deserialize_reader(&mut reader).map_err(ZkvmError::Deserialize)
     }
 
-    fn commit<T: borsh::BorshSerialize>(&self, item: &T) {
-        // use risc0_zkvm::guest::env::Write as _;
-        let buf = borsh::to_vec(item).expect("Serialization to vec is infallible");
+    fn try_commit<T: borsh::BorshSerialize>(&self, item: &T) -> Result<(), ZkvmError> {
+        let buf = borsh::to_vec(item).map_err(ZkvmError::Serialize)?;
         let mut journal = env::journal();
-        journal.write_all(&buf).unwrap();
+        journal.write_all(&buf).map_err(ZkvmError::Journal)
+    }
+
+    fn try_verify<T: borsh::BorshSerialize>(
+        &self,
+        method_id: [u32; 8],
+        output: &T,
+    ) -> Result<(), ZkvmError> {
+        let journal = borsh::to_vec(output).map_err(ZkvmError::Serialize)?;
+        env::verify(method_id, &journal).map_err(|e| ZkvmError::Verify(format!("{:?}", e)))
+    }
+
+    fn cycle_count(&self) -> u64 {
+        env::cycle_count()
+    }
+
+    fn exit(&self, code: GuestExitCode) -> ! {
+        env::exit(code as u8)
+    }
+}
+
+/// [`ZkvmHost`] backed by the local RISC Zero prover. Inputs and assumptions are
+/// buffered until [`ZkvmHost::prove`] or [`ZkvmHost::execute`] builds the executor
+/// environment, so the same host can run a guest more than once.
+#[cfg(feature = "host")]
+pub struct Risc0Host {
+    input: RefCell<Vec<u8>>,
+    assumptions: RefCell<Vec<Receipt>>,
+    opts: ProverOpts,
+    #[cfg(feature = "dev-mode")]
+    dev: bool,
+}
+
+#[cfg(feature = "host")]
+impl Risc0Host {
+    /// Creates a host that proves with `opts`, e.g. [`ProverOpts::succinct`],
+    /// [`ProverOpts::composite`] or [`ProverOpts::groth16`].
+    pub fn new(opts: ProverOpts) -> Self {
+        Self {
+            input: RefCell::new(Vec::new()),
+            assumptions: RefCell::new(Vec::new()),
+            opts,
+            #[cfg(feature = "dev-mode")]
+            dev: false,
+        }
+    }
+
+    /// Creates a host that executes instead of proving and returns fake receipts
+    /// of the successful claim, for pipelines without a real prover. Only a dev
+    /// host accepts them in [`Risc0Host::verify`].
+    #[cfg(feature = "dev-mode")]
+    pub fn dev() -> Self {
+        Self {
+            dev: true,
+            ..Self::new(ProverOpts::default())
+        }
+    }
+
+    /// Verifies a receipt of `image_id`. A dev host also accepts fake receipts
+    /// claiming a successful run with the receipt's journal, without relying on
+    /// `RISC0_DEV_MODE`.
+    pub fn verify(&self, receipt: &Receipt, image_id: [u32; 8]) -> Result<(), VerificationError> {
+        #[cfg(feature = "dev-mode")]
+        if let (true, InnerReceipt::Fake(fake)) = (self.dev, &receipt.inner) {
+            let expected = ReceiptClaim::ok(image_id, receipt.journal.bytes.clone()).digest();
+            let received = fake.claim.digest();
+            if received != expected {
+                return Err(VerificationError::ClaimDigestMismatch { expected, received });
+            }
+            return Ok(());
+        }
+        receipt.verify(image_id)
+    }
+
+    fn env<'a>(&self, stdout: Option<&'a mut Vec<u8>>) -> ExecutorEnv<'a> {
+        let mut builder = ExecutorEnv::builder();
+        for receipt in self.assumptions.borrow().iter() {
+            builder.add_assumption(receipt.clone());
+        }
+        if let Some(stdout) = stdout {
+            builder.stdout(stdout);
+        }
+        builder
+            .write_slice(self.input.borrow().as_slice())
+            .build()
+            .expect("Failed to build executor environment")
+    }
+
+    /// Executes `elf` and collects the guest's profiling spans. Guest output other
+    /// than the spans is forwarded to stdout. The report covers failed runs too,
+    /// up to the point where the guest exited.
+    pub fn execute_with_report(&self, elf: &[u8]) -> (Result<Proof, ZkvmError>, CycleReport) {
+        let mut stdout = Vec::new();
+        let session = default_executor()
+            .execute(self.env(Some(&mut stdout)), elf)
+            .expect("Failed to execute");
+
+        let output = String::from_utf8_lossy(&stdout);
+        output
+            .lines()
+            .filter(|line| !line.starts_with(CYCLES_MARKER))
+            .for_each(|line| println!("{}", line));
+
+        let total_cycles = session
+            .segments
+            .iter()
+            .map(|segment| segment.cycles as u64)
+            .sum();
+        let report = CycleReport::from_guest_output(&output, total_cycles, session.segments.len());
+
+        (executed_proof(elf, session), report)
+    }
+}
+
+/// Journal of a finished run, or the exit code it failed with.
+#[cfg(feature = "host")]
+fn executed_proof(elf: &[u8], session: SessionInfo) -> Result<Proof, ZkvmError> {
+    match session.exit_code {
+        ExitCode::Halted(0) => Ok(Proof {
+            method_id: compute_image_id(elf).expect("Invalid ELF").into(),
+            journal: session.journal.bytes,
+            receipt: None,
+        }),
+        ExitCode::Halted(code) => Err(ZkvmError::Exited(code)),
+        exit_code => Err(ZkvmError::NotHalted(format!("{:?}", exit_code))),
+    }
+}
+
+#[cfg(feature = "host")]
+impl ZkvmHost for Risc0Host {
+    fn write<T: borsh::BorshSerialize>(&self, value: &T) {
+        let buf = borsh::to_vec(value).expect("Serialization to vec is infallible");
+        self.input.borrow_mut().extend_from_slice(&buf);
+    }
+
+    fn add_assumption(&self, proof: Proof) {
+        let receipt = proof
+            .receipt
+            .expect("Only proofs with a receipt can be used as assumptions");
+        self.assumptions.borrow_mut().push(receipt);
+    }
+
+    fn prove(&self, elf: &[u8]) -> Proof {
+        #[cfg(feature = "dev-mode")]
+        if self.dev {
+            let proof = self
+                .execute(elf)
+                .unwrap_or_else(|e| panic!("Failed to prove: {}", e));
+            let claim = ReceiptClaim::ok(proof.method_id, proof.journal.clone());
+            return Proof {
+                receipt: Some(Receipt::new(
+                    InnerReceipt::Fake(FakeReceipt::new(claim)),
+                    proof.journal.clone(),
+                )),
+                ..proof
+            };
+        }
+
+        let receipt = default_prover()
+            .prove_with_opts(self.env(None), elf, &self.opts)
+            .expect("Failed to prove")
+            .receipt;
+
+        Proof {
+            method_id: compute_image_id(elf).expect("Invalid ELF").into(),
+            journal: receipt.journal.bytes.clone(),
+            receipt: Some(receipt),
+        }
+    }
+
+    fn execute(&self, elf: &[u8]) -> Result<Proof, ZkvmError> {
+        let session = default_executor()
+            .execute(self.env(None), elf)
+            .expect("Failed to execute");
+        executed_proof(elf, session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_report_from_guest_output() {
+        let output =
+            "[cycles] WNV 10\nPVK: 5\n[cycles] G16V 20\ntrue\n[cycles] WNV 15\n[cycles] bad";
+        let report = CycleReport::from_guest_output(output, 100, 2);
+
+        assert_eq!(
+            report.stages,
+            vec![("WNV".to_string(), 25), ("G16V".to_string(), 20)]
+        );
+        assert_eq!(report.stage("G16V"), Some(20));
+        assert_eq!(report.stage("PVK"), None);
+        assert_eq!(report.total_cycles, 100);
+        assert_eq!(report.segments, 2);
     }
 
-    fn verify<T: borsh::BorshSerialize>(&self, method_id: [u32; 8], output: &T) {
-        env::verify(method_id, &borsh::to_vec(output).unwrap()).unwrap();
+    #[test]
+    fn test_exit_codes() {
+        for exit_code in GuestExitCode::ALL {
+            assert_eq!(GuestExitCode::from_code(exit_code as u32), Some(exit_code));
+        }
+        assert_eq!(GuestExitCode::from_code(0), None);
+        assert_eq!(
+            ZkvmError::Exited(6).to_string(),
+            "Guest exited with VerificationFailed (6)"
+        );
+        assert_eq!(
+            ZkvmError::Exited(42).to_string(),
+            "Guest exited with code 42"
+        );
     }
 }
@@ -0,0 +1,153 @@
+use crypto_bigint::{Encoding, U256};
+use winternitz_core::journal::Journal;
+use winternitz_core::registry::{pinned_header_chain_method_id, pinned_network_magic};
+use winternitz_core::zkvm::{GuestExitCode, ZkvmGuest};
+use winternitz_core::{ForkChoiceCircuitInput, ForkChoiceCircuitOutput};
+
+/// Compares the total work of two verified header chains. Both chains must start
+/// from the same genesis state, so the heavier one wins the fork choice between them.
+pub fn fork_choice_circuit(guest: &impl ZkvmGuest) {
+    let _span = guest.span("FC");
+    let input: ForkChoiceCircuitInput = match guest.try_read_from_host() {
+        Ok(input) => input,
+        Err(_) => guest.exit(GuestExitCode::InvalidInput),
+    };
+    let header_chain_method_id = pinned_header_chain_method_id();
+    if input.method_id != header_chain_method_id {
+        guest.exit(GuestExitCode::InvalidInput)
+    }
+    for output in [&input.first, &input.second] {
+        if guest.try_verify(input.method_id, output).is_err() {
+            guest.exit(GuestExitCode::AssumptionFailed)
+        }
+    }
+    if input.first.genesis_state_hash != input.second.genesis_state_hash {
+        guest.exit(GuestExitCode::InvalidInput)
+    }
+
+    let first_work = U256::from_be_bytes(input.first.chain_state.total_work);
+    let second_work = U256::from_be_bytes(input.second.chain_state.total_work);
+    // Ties go to the first chain, with a zero work difference.
+    let (heavier, lighter, work_difference) = if first_work >= second_work {
+        (
+            &input.first,
+            &input.second,
+            first_work.wrapping_sub(&second_work),
+        )
+    } else {
+        (
+            &input.second,
+            &input.first,
+            second_work.wrapping_sub(&first_work),
+        )
+    };
+
+    let output = ForkChoiceCircuitOutput {
+        heavier_best_block_hash: heavier.chain_state.best_block_hash,
+        lighter_best_block_hash: lighter.chain_state.best_block_hash,
+        work_difference: work_difference.to_be_bytes(),
+        genesis_state_hash: heavier.genesis_state_hash,
+        header_chain_method_id,
+        network_magic: pinned_network_magic(),
+    };
+    if guest.try_commit(&Journal(&output)).is_err() {
+        guest.exit(GuestExitCode::CommitFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use header_chain::header_chain::{BlockHeaderCircuitOutput, ChainState};
+    use winternitz_core::journal::encode_journal;
+    use winternitz_core::mock::MockGuest;
+
+    fn header_chain_output(work: u8, best_block_hash: [u8; 32]) -> BlockHeaderCircuitOutput {
+        let mut chain_state = ChainState::new();
+        chain_state.total_work[31] = work;
+        chain_state.best_block_hash = best_block_hash;
+        BlockHeaderCircuitOutput {
+            method_id: pinned_header_chain_method_id(),
+            genesis_state_hash: [9; 32],
+            chain_state,
+        }
+    }
+
+    fn fork_choice_input(first_work: u8, second_work: u8) -> ForkChoiceCircuitInput {
+        ForkChoiceCircuitInput {
+            first: header_chain_output(first_work, [1; 32]),
+            second: header_chain_output(second_work, [2; 32]),
+            method_id: pinned_header_chain_method_id(),
+        }
+    }
+
+    /// Runs the circuit with both header-chain outputs as verified assumptions of
+    /// `input.method_id`.
+    fn run(input: &ForkChoiceCircuitInput) -> MockGuest {
+        let guest = MockGuest::new(borsh::to_vec(input).unwrap())
+            .with_assumption(input.method_id, borsh::to_vec(&input.first).unwrap())
+            .with_assumption(input.method_id, borsh::to_vec(&input.second).unwrap());
+        fork_choice_circuit(&guest);
+        guest
+    }
+
+    fn expected_output(
+        heavier_best_block_hash: [u8; 32],
+        lighter_best_block_hash: [u8; 32],
+        work_difference: u8,
+    ) -> Vec<u8> {
+        let mut difference = [0u8; 32];
+        difference[31] = work_difference;
+        encode_journal(&ForkChoiceCircuitOutput {
+            heavier_best_block_hash,
+            lighter_best_block_hash,
+            work_difference: difference,
+            genesis_state_hash: [9; 32],
+            header_chain_method_id: pinned_header_chain_method_id(),
+            network_magic: pinned_network_magic(),
+        })
+    }
+
+    #[test]
+    fn test_fork_choice_circuit_picks_heavier_first_chain() {
+        let guest = run(&fork_choice_input(5, 3));
+        assert_eq!(guest.journal(), expected_output([1; 32], [2; 32], 2));
+    }
+
+    #[test]
+    fn test_fork_choice_circuit_picks_heavier_second_chain() {
+        let guest = run(&fork_choice_input(3, 5));
+        assert_eq!(guest.journal(), expected_output([2; 32], [1; 32], 2));
+    }
+
+    #[test]
+    fn test_fork_choice_circuit_breaks_ties_for_first_chain() {
+        let guest = run(&fork_choice_input(4, 4));
+        assert_eq!(guest.journal(), expected_output([1; 32], [2; 32], 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidInput")]
+    fn test_fork_choice_circuit_rejects_genesis_mismatch() {
+        let mut input = fork_choice_input(5, 3);
+        input.second.genesis_state_hash = [8; 32];
+        run(&input);
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidInput")]
+    fn test_fork_choice_circuit_rejects_unpinned_method_id() {
+        let mut input = fork_choice_input(5, 3);
+        input.method_id = [0; 8];
+        run(&input);
+    }
+
+    #[test]
+    #[should_panic(expected = "AssumptionFailed")]
+    fn test_fork_choice_circuit_requires_both_receipts() {
+        let input = fork_choice_input(5, 3);
+        let guest = MockGuest::new(borsh::to_vec(&input).unwrap())
+            .with_assumption(input.method_id, borsh::to_vec(&input.first).unwrap());
+        fork_choice_circuit(&guest);
+    }
+}
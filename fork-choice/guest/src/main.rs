@@ -0,0 +1,5 @@
+use fork_choice_guest::fork_choice_circuit;
+fn main() {
+    let zkvm_guest = winternitz_core::zkvm::Risc0Guest::new();
+    fork_choice_circuit(&zkvm_guest);
+}
@@ -0,0 +1,259 @@
+//! Cycle counts of the winternitz guest across Winternitz parameters, batch sizes
+//! and seal encodings, plus host-side key generation and signing times.
+//!
+//! Stage cycles are only reported by ELFs built with the `profiling` feature:
+//! `WINTERNITZ_PROFILING=1 REPR_GUEST_BUILD=1 cargo build -p winternitz`.
+//! Run with `cargo bench -p host --bench winternitz -- --output results.json`.
+//!
+//! The guest decodes message digits as bytes, so guest runs always sign with
+//! `log_d = 8`. `--log-d` only varies the host timings, which sign the message
+//! masked to the digit size.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use ark_std::UniformRand;
+use clap::Parser;
+use rand::Rng;
+use risc0_zkvm::{compute_image_id, ProverOpts};
+use serde_json::{json, Value};
+use winternitz_core::constants::BITCOIN_NETWORK;
+use winternitz_core::groth16::{CircuitGroth16Proof, CompressedSealEncoding};
+use winternitz_core::message::{encode_signed_message, SignedChainState};
+use winternitz_core::registry::latest_verifier_entry;
+use winternitz_core::winternitz::{
+    generate_public_key, sign_digits, Parameters, WinternitzCircuitInput, WinternitzGuestInput,
+};
+use winternitz_core::zkvm::{Risc0Host, ZkvmHost};
+
+/// Version of the results document, bumped when a field changes meaning.
+const RESULTS_VERSION: u32 = 3;
+
+#[derive(Parser)]
+struct Args {
+    /// Winternitz `log_d` values for the host timings, in [4, 8].
+    #[arg(long, value_delimiter = ',', default_value = "4,5,6,7,8")]
+    log_d: Vec<u32>,
+    /// Number of signed seals per guest run; 1 runs a single input.
+    #[arg(long, value_delimiter = ',', default_value = "1,2")]
+    batch: Vec<usize>,
+    #[arg(long, value_delimiter = ',', default_value = "ark,bitvm")]
+    encoding: Vec<Encoding>,
+    /// Host-side timing iterations per `log_d`.
+    #[arg(long, default_value_t = 20)]
+    iterations: u32,
+    /// Defaults to the profiling ELF of the compiled network, or its release ELF
+    /// when there is no profiling build.
+    #[arg(long)]
+    elf: Option<PathBuf>,
+    #[arg(long, default_value = "winternitz-bench.json")]
+    output: PathBuf,
+    /// Passed by `cargo bench`.
+    #[arg(long, hide = true)]
+    bench: bool,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Encoding {
+    Ark,
+    Bitvm,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Ark => "ark",
+            Encoding::Bitvm => "bitvm",
+        }
+    }
+}
+
+impl From<Encoding> for CompressedSealEncoding {
+    fn from(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Ark => CompressedSealEncoding::Ark,
+            Encoding::Bitvm => CompressedSealEncoding::BitVm,
+        }
+    }
+}
+
+/// A well-formed seal that proves nothing. The guest runs every stage on it and
+/// exits with `VerificationFailed`, which is all the cycle counts need.
+fn test_seal(rng: &mut impl Rng) -> CircuitGroth16Proof {
+    CircuitGroth16Proof::new(
+        ark_bn254::G1Affine::rand(rng),
+        ark_bn254::G2Affine::rand(rng),
+        ark_bn254::G1Affine::rand(rng),
+    )
+}
+
+/// Winternitz `log_d` the guest decodes: one message byte per digit.
+const GUEST_LOG_D: u32 = 8;
+
+fn test_message(rng: &mut impl Rng, encoding: CompressedSealEncoding) -> Vec<u8> {
+    let chain_state = SignedChainState {
+        total_work: [1; 16],
+        best_block_hash: [2; 32],
+        block_height: 3,
+        policy_digest: [4; 32],
+    };
+    encode_signed_message(&test_seal(rng), &chain_state, encoding).unwrap()
+}
+
+fn signed_input(rng: &mut impl Rng, encoding: CompressedSealEncoding) -> WinternitzCircuitInput {
+    let message = test_message(rng, encoding);
+    let params = Parameters::new(message.len() as u32, GUEST_LOG_D);
+    let secret_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+    WinternitzCircuitInput {
+        pub_key: generate_public_key(&params, &secret_key),
+        signature: sign_digits(&params, &secret_key, &message),
+        params,
+        message,
+        control_root: latest_verifier_entry().control_root,
+        seal_encoding: encoding,
+    }
+}
+
+fn bench_guest(elf: &[u8], batch: usize, encoding: Encoding) -> Value {
+    let mut rng = ark_std::test_rng();
+    let mut inputs: Vec<WinternitzCircuitInput> = (0..batch)
+        .map(|_| signed_input(&mut rng, encoding.into()))
+        .collect();
+    let input = match batch {
+        1 => WinternitzGuestInput::Single(inputs.remove(0)),
+        _ => WinternitzGuestInput::Batch(inputs),
+    };
+
+    let host = Risc0Host::new(ProverOpts::default());
+    host.write(&input);
+    let (result, report) = host.execute_with_report(elf);
+    let outcome = match result {
+        Ok(_) => "committed".to_string(),
+        Err(e) => e.to_string(),
+    };
+    eprintln!(
+        "batch {} {} ({}):\n{}",
+        batch,
+        encoding.name(),
+        outcome,
+        report
+    );
+
+    let stages: serde_json::Map<String, Value> = report
+        .stages
+        .iter()
+        .map(|(stage, cycles)| (stage.clone(), json!(cycles)))
+        .collect();
+    json!({
+        "log_d": GUEST_LOG_D,
+        "batch": batch,
+        "seal_encoding": encoding.name(),
+        "outcome": outcome,
+        "total_cycles": report.total_cycles,
+        "segments": report.segments,
+        "stages": stages,
+    })
+}
+
+/// Minimum and mean of `iterations` runs of `f`, in nanoseconds.
+fn time(iterations: u32, mut f: impl FnMut()) -> Value {
+    let mut min = Duration::MAX;
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f();
+        let elapsed = start.elapsed();
+        min = min.min(elapsed);
+        total += elapsed;
+    }
+    json!({
+        "min_ns": min.as_nanos() as u64,
+        "mean_ns": (total / iterations).as_nanos() as u64,
+    })
+}
+
+fn bench_host(log_d: u32, iterations: u32) -> Value {
+    let mut rng = ark_std::test_rng();
+    let digit_mask = ((1u32 << log_d) - 1) as u8;
+    let message: Vec<u8> = test_message(&mut rng, CompressedSealEncoding::Ark)
+        .iter()
+        .map(|byte| byte & digit_mask)
+        .collect();
+    let params = Parameters::new(message.len() as u32, log_d);
+    let secret_key: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+
+    let generate = time(iterations, || {
+        std::hint::black_box(generate_public_key(&params, &secret_key));
+    });
+    let sign = time(iterations, || {
+        std::hint::black_box(sign_digits(&params, &secret_key, &message));
+    });
+    json!({
+        "log_d": log_d,
+        "iterations": iterations,
+        "generate_public_key": generate,
+        "sign_digits": sign,
+    })
+}
+
+/// Profiling ELF of the compiled network, falling back to the release ELF, which
+/// only reports total cycles.
+fn default_elf() -> PathBuf {
+    let elfs_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../elfs");
+    let profiling = elfs_dir.join(format!("{}-winternitz-profiling-guest", BITCOIN_NETWORK));
+    if profiling.exists() {
+        return profiling;
+    }
+    elfs_dir.join(format!("{}-winternitz-guest", BITCOIN_NETWORK))
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Some(log_d) = args.log_d.iter().find(|log_d| !(4..=8).contains(*log_d)) {
+        eprintln!("log_d must be in [4, 8], got {}", log_d);
+        std::process::exit(2);
+    }
+    if args.iterations == 0 || args.batch.contains(&0) {
+        eprintln!("--iterations and --batch must be positive");
+        std::process::exit(2);
+    }
+
+    let elf_path = args.elf.unwrap_or_else(default_elf);
+    let elf = std::fs::read(&elf_path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", elf_path.display(), e));
+    let image_id = compute_image_id(&elf).expect("Invalid ELF");
+
+    let mut guest = Vec::new();
+    for &batch in &args.batch {
+        for &encoding in &args.encoding {
+            guest.push(bench_guest(&elf, batch, encoding));
+        }
+    }
+    if guest.iter().all(|run| run["stages"]["WNV"].is_null()) {
+        eprintln!(
+            "{} reported no WNV, PVK, PPI or G16V cycles; build it with the `profiling` feature",
+            elf_path.display()
+        );
+    }
+
+    let host: Vec<Value> = args
+        .log_d
+        .iter()
+        .map(|&log_d| bench_host(log_d, args.iterations))
+        .collect();
+
+    let results = json!({
+        "version": RESULTS_VERSION,
+        "network": BITCOIN_NETWORK.to_string(),
+        "elf": elf_path.display().to_string(),
+        "image_id": image_id.to_string(),
+        "guest": guest,
+        "host": host,
+    });
+    std::fs::write(
+        &args.output,
+        serde_json::to_string_pretty(&results).unwrap() + "\n",
+    )
+    .unwrap_or_else(|e| panic!("Failed to write {}: {}", args.output.display(), e));
+    eprintln!("Wrote {}", args.output.display());
+}
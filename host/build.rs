@@ -0,0 +1,38 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// With the `embed-elfs` feature, embeds every guest ELF in `elfs/` so the host
+// runs without an ELF directory. Without it the list is empty.
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let elfs_dir = Path::new(&manifest_dir).join("../elfs");
+    println!("cargo:rerun-if-changed={}", elfs_dir.display());
+
+    let mut entries = Vec::new();
+    if env::var_os("CARGO_FEATURE_EMBED_ELFS").is_some() {
+        for entry in fs::read_dir(&elfs_dir).expect("Failed to read elfs directory") {
+            let path = entry.unwrap().path().canonicalize().unwrap();
+            let file_name = path.file_name().unwrap().to_str().unwrap().to_owned();
+            if !file_name.ends_with("-guest") {
+                continue;
+            }
+            entries.push(format!(
+                "    ({:?}, include_bytes!({:?})),",
+                file_name,
+                path.display().to_string()
+            ));
+        }
+        entries.sort();
+    }
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("embedded_elfs.rs");
+    fs::write(
+        out_path,
+        format!(
+            "pub static EMBEDDED_ELFS: &[(&str, &[u8])] = &[\n{}\n];\n",
+            entries.join("\n")
+        ),
+    )
+    .expect("Failed to write embedded ELF list");
+}
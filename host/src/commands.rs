@@ -0,0 +1,826 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use header_chain::header_chain::{
+    BlockHeaderCircuitOutput, CircuitBlockHeader, HeaderChainCircuitInput, HeaderChainPrevProofType,
+};
+use rand::Rng;
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{compute_image_id, ProverOpts, Receipt};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "dev")]
+use winternitz_core::claim::work_only_public_inputs;
+#[cfg(feature = "dev")]
+use winternitz_core::dev::{dev_prove, DEV_VERIFIER_ENTRY};
+use winternitz_core::groth16::{CircuitGroth16Proof, CompressedSealEncoding};
+use winternitz_core::journal::{decode_journal, parse_journal, CircuitJournal};
+use winternitz_core::json::{
+    Artifact, ArtifactJson, CompressedSealJson, WinternitzPublicKeyJson, WinternitzSecretKeyJson,
+};
+use winternitz_core::message::{
+    decode_signed_message, encode_signed_message, SignedChainState, COMPRESSED_SEAL_SIZE,
+    SIGNED_MESSAGE_SIZE,
+};
+use winternitz_core::policy::WorkOnlyPolicy;
+use winternitz_core::registry::{
+    header_chain_method_id, latest_verifier_entry, Risc0VerifierEntry, HEADER_CHAIN_METHOD_IDS,
+    WORK_ONLY_METHOD_IDS,
+};
+use winternitz_core::winternitz::{
+    generate_public_key, sign_digits, Parameters, PublicKey, SecretKey, WinternitzCircuitInput,
+    WinternitzGuestInput,
+};
+use winternitz_core::zkvm::{Proof, Risc0Host, ZkvmHost};
+use winternitz_core::{ForkChoiceCircuitInput, WorkOnlyCircuitInput, WorkOnlyCircuitOutput};
+
+use crate::headers::{HeaderSource, RawHeaderFile};
+use crate::manifest::{check_work_only_pinned, Manifest, ManifestError};
+use crate::store::{ArtifactKey, ArtifactKind, ArtifactStore};
+use crate::{Circuit, ExportSource, Network};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+include!(concat!(env!("OUT_DIR"), "/embedded_elfs.rs"));
+
+/// Guest ELFs of one network, read from `<dir>/<network>-<circuit>-guest` or, when
+/// missing there, from the ELFs embedded at build time. In dev mode the winternitz
+/// guest is the `<network>-winternitz-dev-guest` build accepting dev seals.
+pub struct Elfs {
+    dir: PathBuf,
+    network: Network,
+    dev: bool,
+}
+
+impl Elfs {
+    pub fn new(dir: PathBuf, network: Network, dev: bool) -> Self {
+        Self { dir, network, dev }
+    }
+
+    /// The same ELF directory, for another network.
+    pub fn with_network(&self, network: Network) -> Self {
+        Self::new(self.dir.clone(), network, self.dev)
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn dev(&self) -> bool {
+        self.dev
+    }
+
+    fn file_name(&self, circuit: Circuit) -> String {
+        if self.dev && circuit == Circuit::Winternitz {
+            return format!("{}-winternitz-dev-guest", self.network.name());
+        }
+        format!("{}-{}-guest", self.network.name(), circuit.name())
+    }
+
+    pub fn path(&self, circuit: Circuit) -> PathBuf {
+        self.dir.join(self.file_name(circuit))
+    }
+
+    pub fn load(&self, circuit: Circuit) -> Result<Vec<u8>> {
+        let path = self.path(circuit);
+        match fs::read(&path) {
+            Ok(elf) => Ok(elf),
+            Err(e) => EMBEDDED_ELFS
+                .iter()
+                .find(|(name, _)| *name == self.file_name(circuit))
+                .map(|(_, elf)| elf.to_vec())
+                .ok_or_else(|| format!("Failed to read {}: {}", path.display(), e).into()),
+        }
+    }
+
+    pub fn image_id(&self, circuit: Circuit) -> Result<[u32; 8]> {
+        Ok(compute_image_id(&self.load(circuit)?)?.into())
+    }
+
+    /// Host proving with `opts`. Dev runs get fake receipts instead of proofs.
+    pub fn host(&self, opts: ProverOpts) -> Risc0Host {
+        #[cfg(feature = "dev")]
+        if self.dev {
+            return Risc0Host::dev();
+        }
+        Risc0Host::new(opts)
+    }
+}
+
+/// Reads a bincode encoded receipt of the circuit with `method_id`.
+fn read_proof(path: &Path, method_id: [u32; 8]) -> Result<Proof> {
+    let receipt: Receipt = bincode::deserialize(&fs::read(path)?)?;
+    Ok(Proof {
+        method_id,
+        journal: receipt.journal.bytes.clone(),
+        receipt: Some(receipt),
+    })
+}
+
+fn write_proof(path: &Path, proof: &Proof) -> Result<()> {
+    let receipt = proof.receipt.as_ref().ok_or("Proof has no receipt")?;
+    fs::write(path, bincode::serialize(receipt)?)?;
+    Ok(())
+}
+
+/// Checks that a work-only output was proven for the network of `elfs`. The
+/// winternitz guest rebuilds the work-only journal with the network magic and
+/// header-chain image ID it was built for, so a mismatch could never verify. The
+/// header-chain image ID of each network is the one the guests were built with,
+/// from the generated table in `winternitz_core::registry`.
+fn check_work_only_network(elfs: &Elfs, output: &WorkOnlyCircuitOutput) -> Result<()> {
+    let network = elfs.network();
+    if output.network_magic != network.magic() {
+        let proven = Network::from_magic(output.network_magic).map_or_else(
+            || hex::encode(output.network_magic),
+            |n| n.name().to_string(),
+        );
+        return Err(format!(
+            "Work-only receipt is for {}, but the guests are for {}",
+            proven,
+            network.name()
+        )
+        .into());
+    }
+    let pinned = header_chain_method_id(network.name())
+        .ok_or_else(|| format!("No header-chain image ID for {}", network.name()))?;
+    if output.header_chain_method_id != pinned {
+        return Err(format!(
+            "Work-only receipt was proven against header-chain image {}, but {} pins {}",
+            hex::encode(digest_bytes(output.header_chain_method_id)),
+            network.name(),
+            hex::encode(digest_bytes(pinned))
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn read_guest_input(inputs: &[PathBuf]) -> Result<WinternitzGuestInput> {
+    let mut inputs = inputs
+        .iter()
+        .map(|path| -> Result<WinternitzCircuitInput> {
+            Ok(WinternitzCircuitInput::try_from_slice(&fs::read(path)?)?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(match inputs.len() {
+        1 => WinternitzGuestInput::Single(inputs.remove(0)),
+        _ => WinternitzGuestInput::Batch(inputs),
+    })
+}
+
+fn digest_bytes(image_id: [u32; 8]) -> [u8; 32] {
+    Digest::from(image_id).as_bytes().try_into().unwrap()
+}
+
+/// Prints the image IDs of the ELFs and, with `write_manifest`, records them in
+/// the manifest at that path, keeping the entries of other networks.
+pub fn image_ids(elfs: &Elfs, write_manifest: Option<&Path>) -> Result<()> {
+    if elfs.dev() && write_manifest.is_some() {
+        return Err("The manifest records release ELFs, not dev ELFs".into());
+    }
+    let mut manifest = match write_manifest {
+        Some(path) if path.exists() => Manifest::parse(&fs::read_to_string(path)?)?,
+        _ => Manifest::embedded()?,
+    };
+    for circuit in Circuit::ALL {
+        match elfs.image_id(circuit) {
+            Ok(image_id) => {
+                println!(
+                    "{}: {:?} ({})",
+                    circuit.name(),
+                    image_id,
+                    hex::encode(digest_bytes(image_id))
+                );
+                manifest.set(elfs.network(), circuit, digest_bytes(image_id));
+            }
+            Err(e) => println!("{}: {}", circuit.name(), e),
+        }
+    }
+    if let Some(path) = write_manifest {
+        fs::write(path, manifest.to_json())?;
+    }
+    Ok(())
+}
+
+/// Checks the image IDs of the available ELFs against the manifest, and that the
+/// winternitz guest verifies proofs of the work-only ELF. Missing ELFs are left
+/// to the commands that load them. Dev ELFs are not in the manifest.
+pub fn check_image_ids(elfs: &Elfs) -> Result<()> {
+    let manifest = Manifest::embedded()?;
+    if !elfs.dev() {
+        for circuit in Circuit::ALL {
+            if let Ok(image_id) = elfs.image_id(circuit) {
+                manifest.check(elfs.network(), circuit, digest_bytes(image_id))?;
+            }
+        }
+    }
+    if let Ok(work_only_id) = elfs.image_id(Circuit::WorkOnly) {
+        check_work_only_pinned(elfs.network(), work_only_id)?;
+    }
+    Ok(())
+}
+
+/// Proves `elf` on `input`. With a store, a receipt already proven from the same
+/// input is reused and new receipts are saved, so failed runs restart from the
+/// last good stage.
+fn prove_with_store<T: BorshSerialize>(
+    store: Option<&ArtifactStore>,
+    host: &Risc0Host,
+    elf: &[u8],
+    input: &T,
+) -> Result<Proof> {
+    let method_id: [u32; 8] = compute_image_id(elf)?.into();
+    let input_bytes = borsh::to_vec(input)?;
+    // Fake receipts of dev runs only verify on a dev host, so they are never
+    // reused for real proofs.
+    if let Some(receipt) = store
+        .map(|store| store.find_proof(method_id, &input_bytes))
+        .transpose()?
+        .flatten()
+        .filter(|receipt| host.verify(receipt, method_id).is_ok())
+    {
+        println!("Reusing stored receipt for image ID {:?}", method_id);
+        return Ok(Proof {
+            method_id,
+            journal: receipt.journal.bytes.clone(),
+            receipt: Some(receipt),
+        });
+    }
+
+    host.write(input);
+    let proof = host.prove(elf);
+    if let Some(store) = store {
+        let receipt = proof.receipt.as_ref().ok_or("Proof has no receipt")?;
+        store.put_proof(method_id, &input_bytes, receipt)?;
+    }
+    Ok(proof)
+}
+
+/// Proves `block_headers` on top of `prev_proof`, or on top of genesis without one.
+fn prove_headerchain_batch(
+    store: Option<&ArtifactStore>,
+    host: &Risc0Host,
+    elf: &[u8],
+    prev_proof: Option<Proof>,
+    block_headers: Vec<CircuitBlockHeader>,
+) -> Result<Proof> {
+    let method_id: [u32; 8] = compute_image_id(elf)?.into();
+
+    let prev_proof = match prev_proof {
+        Some(proof) => {
+            let prev_output = BlockHeaderCircuitOutput::try_from_slice(&proof.journal)?;
+            host.add_assumption(proof);
+            HeaderChainPrevProofType::PrevProof(prev_output)
+        }
+        None => HeaderChainPrevProofType::GenesisBlock,
+    };
+    let input = HeaderChainCircuitInput {
+        method_id,
+        prev_proof,
+        block_headers,
+    };
+    prove_with_store(store, host, elf, &input)
+}
+
+pub fn prove_headerchain(
+    elfs: &Elfs,
+    store: Option<&ArtifactStore>,
+    source: &mut dyn HeaderSource,
+    start: usize,
+    count: usize,
+    prev_proof: Option<&Path>,
+    output: &Path,
+) -> Result<()> {
+    let elf = elfs.load(Circuit::Headerchain)?;
+    let prev_proof = match prev_proof {
+        Some(path) => Some(read_proof(path, compute_image_id(&elf)?.into())?),
+        None => None,
+    };
+
+    let host = elfs.host(ProverOpts::succinct());
+    let proof = prove_headerchain_batch(
+        store,
+        &host,
+        &elf,
+        prev_proof,
+        source.headers(start, count)?,
+    )?;
+    write_proof(output, &proof)
+}
+
+/// Receipts of [`prove_headerchain_incremental`] are named after the number of
+/// headers they cover.
+fn headerchain_receipt_path(receipts_dir: &Path, proven: usize) -> PathBuf {
+    receipts_dir.join(format!("headerchain-{:08}.receipt", proven))
+}
+
+/// Returns the number of headers covered by the latest receipt in `receipts_dir`.
+fn latest_headerchain_receipt(receipts_dir: &Path) -> Result<Option<usize>> {
+    let mut latest = None;
+    for entry in fs::read_dir(receipts_dir)? {
+        let file_name = entry?.file_name();
+        let proven = file_name
+            .to_str()
+            .and_then(|name| name.strip_prefix("headerchain-"))
+            .and_then(|name| name.strip_suffix(".receipt"))
+            .and_then(|proven| proven.parse::<usize>().ok());
+        latest = latest.max(proven);
+    }
+    Ok(latest)
+}
+
+/// Proves the headers in batches, each on top of the receipt of the previous
+/// batch. Every receipt is saved to `receipts_dir` before the next batch starts,
+/// so an interrupted run resumes from the latest one.
+pub fn prove_headerchain_incremental(
+    elfs: &Elfs,
+    store: Option<&ArtifactStore>,
+    source: &mut dyn HeaderSource,
+    batch_size: usize,
+    receipts_dir: &Path,
+) -> Result<()> {
+    if batch_size == 0 {
+        return Err("Batch size must be positive".into());
+    }
+    let elf = elfs.load(Circuit::Headerchain)?;
+    let method_id: [u32; 8] = compute_image_id(&elf)?.into();
+    let headers = source.headers(0, usize::MAX)?;
+    fs::create_dir_all(receipts_dir)?;
+
+    let (mut proven, mut prev_proof) = match latest_headerchain_receipt(receipts_dir)? {
+        Some(proven) => {
+            let proof = read_proof(&headerchain_receipt_path(receipts_dir, proven), method_id)?;
+            let output = BlockHeaderCircuitOutput::try_from_slice(&proof.journal)?;
+            let resumes_headers = proven > 0
+                && proven <= headers.len()
+                && headers[proven - 1].compute_block_hash() == output.chain_state.best_block_hash;
+            if !resumes_headers {
+                return Err(format!(
+                    "Latest receipt in {} does not match the headers",
+                    receipts_dir.display()
+                )
+                .into());
+            }
+            println!("Resuming after {} proven headers", proven);
+            (proven, Some(proof))
+        }
+        None => (0, None),
+    };
+
+    while proven < headers.len() {
+        let end = (proven + batch_size).min(headers.len());
+        let host = elfs.host(ProverOpts::succinct());
+        let proof = prove_headerchain_batch(
+            store,
+            &host,
+            &elf,
+            prev_proof,
+            headers[proven..end].to_vec(),
+        )?;
+
+        // Write to a temporary file first so a crash never leaves a partial receipt.
+        let path = headerchain_receipt_path(receipts_dir, end);
+        let tmp_path = path.with_extension("tmp");
+        write_proof(&tmp_path, &proof)?;
+        fs::rename(&tmp_path, &path)?;
+        println!("Proved headers {}..{}", proven, end);
+
+        proven = end;
+        prev_proof = Some(proof);
+    }
+    Ok(())
+}
+
+/// Reads a work-only policy exported as a versioned JSON artifact.
+fn read_policy(path: &Path) -> Result<WorkOnlyPolicy> {
+    let document: ArtifactJson = serde_json::from_slice(&fs::read(path)?)?;
+    match document.into_artifact()? {
+        Artifact::WorkOnlyPolicy(policy) => Ok(WorkOnlyPolicy::try_from(&policy)?),
+        _ => Err(format!("{} is not a work-only policy", path.display()).into()),
+    }
+}
+
+pub fn prove_work_only(
+    elfs: &Elfs,
+    store: Option<&ArtifactStore>,
+    headerchain_proof: &Path,
+    policy: Option<&Path>,
+    confirmation_headers: Option<&Path>,
+    output: &Path,
+) -> Result<()> {
+    let headerchain_id = elfs.image_id(Circuit::Headerchain)?;
+    let proof = read_proof(headerchain_proof, headerchain_id)?;
+    let policy = match policy {
+        Some(path) => read_policy(path)?,
+        None => WorkOnlyPolicy::default(),
+    };
+    let confirmation_headers = match confirmation_headers {
+        Some(path) => RawHeaderFile::new(path.to_path_buf()).headers(0, usize::MAX)?,
+        None => vec![],
+    };
+    let input = WorkOnlyCircuitInput {
+        header_chain_circuit_output: BlockHeaderCircuitOutput::try_from_slice(&proof.journal)?,
+        method_id: headerchain_id,
+        policy,
+        confirmation_headers,
+    };
+
+    let host = elfs.host(ProverOpts::groth16());
+    host.add_assumption(proof);
+    let proof = prove_with_store(store, &host, &elfs.load(Circuit::WorkOnly)?, &input)?;
+    write_proof(output, &proof)
+}
+
+pub fn prove_fork_choice(
+    elfs: &Elfs,
+    store: Option<&ArtifactStore>,
+    first_proof: &Path,
+    second_proof: &Path,
+    output: &Path,
+) -> Result<()> {
+    let headerchain_id = elfs.image_id(Circuit::Headerchain)?;
+    let first = read_proof(first_proof, headerchain_id)?;
+    let second = read_proof(second_proof, headerchain_id)?;
+    let input = ForkChoiceCircuitInput {
+        first: BlockHeaderCircuitOutput::try_from_slice(&first.journal)?,
+        second: BlockHeaderCircuitOutput::try_from_slice(&second.journal)?,
+        method_id: headerchain_id,
+    };
+
+    let host = elfs.host(ProverOpts::groth16());
+    host.add_assumption(first);
+    host.add_assumption(second);
+    let proof = prove_with_store(store, &host, &elfs.load(Circuit::ForkChoice)?, &input)?;
+    write_proof(output, &proof)
+}
+
+/// Key files hold the Borsh encoded Winternitz parameters followed by the key.
+/// Existing key files are only replaced with `force`.
+pub fn keygen(
+    log_d: u32,
+    secret_key_path: &Path,
+    public_key_path: &Path,
+    force: bool,
+) -> Result<()> {
+    if !force {
+        if let Some(path) = [secret_key_path, public_key_path]
+            .into_iter()
+            .find(|path| path.exists())
+        {
+            return Err(format!("{} exists, pass --force to replace it", path.display()).into());
+        }
+    }
+    let params = Parameters::new(SIGNED_MESSAGE_SIZE as u32, log_d);
+    let mut rng = rand::thread_rng();
+    let secret_key: SecretKey = (0..SIGNED_MESSAGE_SIZE).map(|_| rng.gen()).collect();
+    let public_key: PublicKey = generate_public_key(&params, &secret_key);
+
+    write_secret(
+        secret_key_path,
+        &borsh::to_vec(&(&params, &secret_key))?,
+        force,
+    )?;
+    fs::write(public_key_path, borsh::to_vec(&(&params, &public_key))?)?;
+    Ok(())
+}
+
+/// Writes a file only its owner can read. Without `overwrite`, an existing file is
+/// left alone and an error returned.
+fn write_secret(path: &Path, bytes: &[u8], overwrite: bool) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    if overwrite {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    // The mode only applies to new files.
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(bytes)?;
+    Ok(())
+}
+
+/// With a store, the compressed seal, public key and signature are saved next to
+/// the work-only receipt.
+pub fn sign(
+    elfs: &Elfs,
+    store: Option<&ArtifactStore>,
+    work_only_proof: &Path,
+    secret_key: &Path,
+    seal_encoding: CompressedSealEncoding,
+    output: &Path,
+) -> Result<()> {
+    let proof = read_proof(work_only_proof, elfs.image_id(Circuit::WorkOnly)?)?;
+    let work_only_output: WorkOnlyCircuitOutput = decode_journal(&proof.journal)?;
+    check_work_only_network(elfs, &work_only_output)?;
+    let (seal, verifier) = match dev_seal(elfs, &work_only_output)? {
+        Some(dev_seal) => dev_seal,
+        None => {
+            let receipt = proof.receipt.as_ref().ok_or("Proof has no receipt")?;
+            (groth16_seal(receipt)?, latest_verifier_entry())
+        }
+    };
+    let message = encode_signed_message(
+        &seal,
+        &SignedChainState::from(&work_only_output),
+        seal_encoding,
+    )?;
+
+    let (params, secret_key): (Parameters, SecretKey) =
+        BorshDeserialize::try_from_slice(&fs::read(secret_key)?)?;
+    let input = WinternitzCircuitInput {
+        pub_key: generate_public_key(&params, &secret_key),
+        signature: sign_digits(&params, &secret_key, &message),
+        params,
+        message,
+        control_root: verifier.control_root,
+        seal_encoding,
+    };
+    if let Some(store) = store {
+        let key = ArtifactKey::new(proof.method_id, &proof.journal);
+        store.put(
+            &key,
+            ArtifactKind::CompressedSeal,
+            &input.message[..COMPRESSED_SEAL_SIZE],
+        )?;
+        store.put(
+            &key,
+            ArtifactKind::WinternitzPublicKey,
+            &borsh::to_vec(&input.pub_key)?,
+        )?;
+        store.put(
+            &key,
+            ArtifactKind::WinternitzSignature,
+            &borsh::to_vec(&input.signature)?,
+        )?;
+    }
+    fs::write(output, borsh::to_vec(&input)?)?;
+    Ok(())
+}
+
+/// In dev runs, a seal of the test setup over the work-only claim, since fake
+/// receipts have no seal of their own.
+#[cfg(feature = "dev")]
+fn dev_seal(
+    elfs: &Elfs,
+    output: &WorkOnlyCircuitOutput,
+) -> Result<Option<(CircuitGroth16Proof, &'static Risc0VerifierEntry)>> {
+    if !elfs.dev() {
+        return Ok(None);
+    }
+    let public_inputs = work_only_public_inputs(output, &DEV_VERIFIER_ENTRY);
+    Ok(Some((dev_prove(&public_inputs)?, &DEV_VERIFIER_ENTRY)))
+}
+
+#[cfg(not(feature = "dev"))]
+fn dev_seal(
+    _elfs: &Elfs,
+    _output: &WorkOnlyCircuitOutput,
+) -> Result<Option<(CircuitGroth16Proof, &'static Risc0VerifierEntry)>> {
+    Ok(None)
+}
+
+pub fn verify_signature(input: &Path) -> Result<()> {
+    let input = WinternitzCircuitInput::try_from_slice(&fs::read(input)?)?;
+    if !winternitz_core::winternitz::verify_signature(&input) {
+        return Err("Invalid Winternitz signature".into());
+    }
+    println!("Winternitz signature is valid");
+    Ok(())
+}
+
+pub fn execute_winternitz(elfs: &Elfs, inputs: &[PathBuf]) -> Result<()> {
+    let host = Risc0Host::new(ProverOpts::succinct());
+    host.write(&read_guest_input(inputs)?);
+
+    let (proof, report) = host.execute_with_report(&elfs.load(Circuit::Winternitz)?);
+    println!("WINTERNITZ CYCLES:\n{}", report);
+    println!("JOURNAL: {:?}", parse_journal(&proof?.journal)?);
+    Ok(())
+}
+
+pub fn prove_winternitz(
+    elfs: &Elfs,
+    store: Option<&ArtifactStore>,
+    inputs: &[PathBuf],
+    output: &Path,
+) -> Result<()> {
+    let host = elfs.host(ProverOpts::succinct());
+    let proof = prove_with_store(
+        store,
+        &host,
+        &elfs.load(Circuit::Winternitz)?,
+        &read_guest_input(inputs)?,
+    )?;
+    write_proof(output, &proof)
+}
+
+pub fn verify_receipt(elfs: &Elfs, circuit: Circuit, receipt: &Path) -> Result<()> {
+    let proof = read_proof(receipt, elfs.image_id(circuit)?)?;
+    elfs.host(ProverOpts::default()).verify(
+        proof.receipt.as_ref().ok_or("Proof has no receipt")?,
+        proof.method_id,
+    )?;
+
+    println!("Receipt is valid for {}", circuit.name());
+    match circuit {
+        Circuit::Headerchain => println!(
+            "JOURNAL: {:?}",
+            BlockHeaderCircuitOutput::try_from_slice(&proof.journal)?
+        ),
+        _ => println!("JOURNAL: {:?}", parse_journal(&proof.journal)?),
+    }
+    Ok(())
+}
+
+/// Groth16 seal of a work-only receipt.
+fn groth16_seal(receipt: &Receipt) -> Result<CircuitGroth16Proof> {
+    let groth16_receipt = receipt.inner.groth16()?;
+    Ok(CircuitGroth16Proof::from_seal(
+        groth16_receipt
+            .seal
+            .as_slice()
+            .try_into()
+            .map_err(|_| "Unexpected Groth16 seal size")?,
+    ))
+}
+
+pub fn export(from: ExportSource, input: &Path, output: Option<&Path>) -> Result<()> {
+    let bytes = fs::read(input)?;
+    let artifact = match from {
+        ExportSource::PublicKey => {
+            let (params, pub_key): (Parameters, PublicKey) =
+                BorshDeserialize::try_from_slice(&bytes)?;
+            Artifact::WinternitzPublicKey(WinternitzPublicKeyJson::new(&params, &pub_key))
+        }
+        ExportSource::SecretKey => {
+            let (params, secret_key): (Parameters, SecretKey) =
+                BorshDeserialize::try_from_slice(&bytes)?;
+            Artifact::WinternitzSecretKey(WinternitzSecretKeyJson::new(&params, &secret_key))
+        }
+        ExportSource::WinternitzInput => Artifact::WinternitzCircuitInput(
+            (&WinternitzCircuitInput::try_from_slice(&bytes)?).into(),
+        ),
+        ExportSource::CompressedSeal => {
+            let input = WinternitzCircuitInput::try_from_slice(&bytes)?;
+            let (seal, _) = decode_signed_message(&input.message, input.seal_encoding)?;
+            Artifact::CompressedSeal(CompressedSealJson::new(&seal, input.seal_encoding)?)
+        }
+        ExportSource::Seal => {
+            let receipt: Receipt = bincode::deserialize(&bytes)?;
+            Artifact::Groth16Proof((&groth16_seal(&receipt)?).into())
+        }
+        ExportSource::Receipt => {
+            let receipt: Receipt = bincode::deserialize(&bytes)?;
+            match parse_journal(&receipt.journal.bytes)? {
+                CircuitJournal::WorkOnly(output) => Artifact::WorkOnlyOutput((&output).into()),
+                CircuitJournal::ForkChoice(output) => Artifact::ForkChoiceOutput((&output).into()),
+                CircuitJournal::Winternitz(output) => Artifact::WinternitzOutput((&output).into()),
+                CircuitJournal::WinternitzBatch(output) => {
+                    Artifact::WinternitzBatchOutput((&output).try_into()?)
+                }
+            }
+        }
+    };
+
+    let json = serde_json::to_string_pretty(&ArtifactJson::new(artifact))?;
+    match output {
+        Some(path) => fs::write(path, json + "\n")?,
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+pub fn import(input: &Path, output: &Path) -> Result<()> {
+    let document: ArtifactJson = serde_json::from_str(&fs::read_to_string(input)?)?;
+    let bytes = match document.into_artifact()? {
+        Artifact::WinternitzPublicKey(key) => borsh::to_vec(&key.decode()?)?,
+        Artifact::WinternitzSecretKey(key) => {
+            return write_secret(output, &borsh::to_vec(&key.decode()?)?, true)
+        }
+        Artifact::WinternitzCircuitInput(input) => {
+            borsh::to_vec(&WinternitzCircuitInput::try_from(&input)?)?
+        }
+        Artifact::Groth16Proof(proof) => CircuitGroth16Proof::try_from(&proof)?.to_seal().to_vec(),
+        Artifact::CompressedSeal(seal) => {
+            seal.decode()?.to_compressed_with(seal.encoding)?.to_vec()
+        }
+        Artifact::WorkOnlyPolicy(_) => {
+            return Err("Work-only policies are read as JSON by prove-work-only".into())
+        }
+        Artifact::WorkOnlyOutput(_)
+        | Artifact::ForkChoiceOutput(_)
+        | Artifact::WinternitzOutput(_)
+        | Artifact::WinternitzBatchOutput(_) => {
+            return Err("Guest outputs come from receipts and can only be exported".into())
+        }
+    };
+    fs::write(output, bytes)?;
+    Ok(())
+}
+
+pub fn store_list(store: &ArtifactStore) -> Result<()> {
+    for (key, kinds) in store.list()? {
+        let kinds: Vec<&str> = kinds.iter().map(|kind| kind.name()).collect();
+        println!(
+            "{:?} {}: {}",
+            key.image_id,
+            hex::encode(key.journal_digest),
+            kinds.join(", ")
+        );
+    }
+    Ok(())
+}
+
+pub fn store_verify(store: &ArtifactStore) -> Result<()> {
+    let corrupted = store.verify()?;
+    for (key, kind) in &corrupted {
+        println!(
+            "{:?} {}: {} is corrupted",
+            key.image_id,
+            hex::encode(key.journal_digest),
+            kind.name()
+        );
+    }
+    if !corrupted.is_empty() {
+        return Err(format!("{} corrupted artifacts", corrupted.len()).into());
+    }
+    println!("All artifacts are intact");
+    Ok(())
+}
+
+/// Keeps the artifacts of every guest in the manifest, of the header-chain and
+/// work-only images in the generated tables and, with `--dev`, of the dev
+/// winternitz guests. Refuses while an ELF of the manifest is missing, so a partial
+/// ELF directory never empties the store.
+pub fn store_gc(elfs: &Elfs, store: &ArtifactStore, dry_run: bool) -> Result<()> {
+    let manifest = Manifest::embedded()?;
+    let mut live_image_ids: Vec<[u32; 8]> = HEADER_CHAIN_METHOD_IDS
+        .iter()
+        .chain(WORK_ONLY_METHOD_IDS)
+        .map(|(_, image_id)| *image_id)
+        .collect();
+    for network in Network::ALL {
+        let release = Elfs::new(elfs.dir.clone(), network, false);
+        for circuit in Circuit::ALL {
+            let image_id = match manifest.image_id(network, circuit) {
+                Ok(image_id) => image_id,
+                Err(ManifestError::Missing { .. }) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            release
+                .load(circuit)
+                .map_err(|e| format!("Refusing to collect garbage: {}", e))?;
+            live_image_ids.push(Digest::from(image_id).into());
+        }
+        if elfs.dev() {
+            if let Ok(image_id) = elfs.with_network(network).image_id(Circuit::Winternitz) {
+                live_image_ids.push(image_id);
+            }
+        }
+    }
+
+    let report = store.gc(&live_image_ids, dry_run)?;
+    println!(
+        "{} {} entries and {} files",
+        if dry_run { "Would remove" } else { "Removed" },
+        report.removed_entries,
+        report.removed_files
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keygen_protects_secret_key() {
+        let dir = std::env::temp_dir().join(format!("keygen-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (secret_key, public_key) = (dir.join("secret.key"), dir.join("public.key"));
+
+        keygen(8, &secret_key, &public_key, false).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&secret_key).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let key = fs::read(&secret_key).unwrap();
+        assert!(keygen(8, &secret_key, &public_key, false).is_err());
+        assert_eq!(fs::read(&secret_key).unwrap(), key);
+
+        keygen(8, &secret_key, &public_key, true).unwrap();
+        assert_ne!(fs::read(&secret_key).unwrap(), key);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,450 @@
+use base64::Engine;
+use borsh::BorshDeserialize;
+use header_chain::header_chain::CircuitBlockHeader;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+pub const HEADER_SIZE: usize = 80;
+
+/// Where the host reads block headers from before proving them.
+pub trait HeaderSource {
+    /// Returns up to `count` headers of the best chain, starting at height `start`.
+    fn read_headers(&mut self, start: usize, count: usize) -> Result<Vec<CircuitBlockHeader>>;
+
+    /// [`HeaderSource::read_headers`], checked to form a chain.
+    fn headers(&mut self, start: usize, count: usize) -> Result<Vec<CircuitBlockHeader>> {
+        let headers = self.read_headers(start, count)?;
+        check_linked(&headers)?;
+        Ok(headers)
+    }
+}
+
+/// Checks that every header builds on the one before it.
+pub fn check_linked(headers: &[CircuitBlockHeader]) -> Result<()> {
+    for (i, pair) in headers.windows(2).enumerate() {
+        if pair[1].prev_block_hash != pair[0].compute_block_hash() {
+            return Err(format!("Header {} does not build on header {}", i + 1, i).into());
+        }
+    }
+    Ok(())
+}
+
+fn parse_headers(bytes: &[u8]) -> Result<Vec<CircuitBlockHeader>> {
+    if !bytes.len().is_multiple_of(HEADER_SIZE) {
+        return Err("Input is not a list of 80-byte headers".into());
+    }
+    Ok(bytes
+        .chunks(HEADER_SIZE)
+        .map(CircuitBlockHeader::try_from_slice)
+        .collect::<std::io::Result<_>>()?)
+}
+
+/// Concatenated raw 80-byte headers, starting at genesis.
+pub struct RawHeaderFile {
+    path: PathBuf,
+}
+
+impl RawHeaderFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl HeaderSource for RawHeaderFile {
+    fn read_headers(&mut self, start: usize, count: usize) -> Result<Vec<CircuitBlockHeader>> {
+        let headers = parse_headers(&fs::read(&self.path)?)
+            .map_err(|e| format!("{}: {}", self.path.display(), e))?;
+        Ok(headers.into_iter().skip(start).take(count).collect())
+    }
+}
+
+/// Bitcoin Core `blk*.dat` files. Blocks are stored in the order they were
+/// received, so the best chain is rebuilt from the block links, following the
+/// branch from genesis with the most cumulative work.
+pub struct BlockFiles {
+    blocks_dir: PathBuf,
+    network_magic: [u8; 4],
+}
+
+impl BlockFiles {
+    pub fn new(blocks_dir: PathBuf, network_magic: [u8; 4]) -> Self {
+        Self {
+            blocks_dir,
+            network_magic,
+        }
+    }
+
+    /// Key Bitcoin Core obfuscates block files with, from `xor.dat` if present.
+    fn xor_key(&self) -> Result<Option<Vec<u8>>> {
+        let path = self.blocks_dir.join("xor.dat");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let key = fs::read(path)?;
+        Ok(key.iter().any(|byte| *byte != 0).then_some(key))
+    }
+
+    fn read_file(&self, path: &Path, xor_key: Option<&[u8]>) -> Result<Vec<CircuitBlockHeader>> {
+        let mut bytes = fs::read(path)?;
+        if let Some(key) = xor_key {
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte ^= key[i % key.len()];
+            }
+        }
+
+        let mut headers = Vec::new();
+        let mut offset = 0;
+        // Files are preallocated, so zeroed space ends the records.
+        while offset + 8 + HEADER_SIZE <= bytes.len() && bytes[offset..offset + 4] != [0; 4] {
+            if bytes[offset..offset + 4] != self.network_magic {
+                return Err(format!("Bad magic at {}:{}", path.display(), offset).into());
+            }
+            let size =
+                u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap()) as usize;
+            if size < HEADER_SIZE || size > bytes.len() - offset - 8 {
+                return Err(
+                    format!("Bad block size {} at {}:{}", size, path.display(), offset).into(),
+                );
+            }
+            let block = &bytes[offset + 8..offset + 8 + size];
+            headers.push(CircuitBlockHeader::try_from_slice(&block[..HEADER_SIZE])?);
+            offset += 8 + size;
+        }
+        Ok(headers)
+    }
+
+    fn best_chain(&self) -> Result<Vec<CircuitBlockHeader>> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.blocks_dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<_>>()?;
+        paths.retain(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("blk") && name.ends_with(".dat"))
+        });
+        paths.sort();
+
+        let xor_key = self.xor_key()?;
+        // Blocks with the position they were first read at.
+        let mut by_hash: HashMap<[u8; 32], (usize, CircuitBlockHeader)> = HashMap::new();
+        for path in paths {
+            for header in self.read_file(&path, xor_key.as_deref())? {
+                let position = by_hash.len();
+                by_hash
+                    .entry(header.compute_block_hash())
+                    .or_insert((position, header));
+            }
+        }
+
+        // Cumulative work of every block whose ancestors reach genesis.
+        let mut chain_work: HashMap<[u8; 32], BigUint> = HashMap::new();
+        for hash in by_hash.keys() {
+            let mut path = vec![*hash];
+            let parent_work = loop {
+                let (_, header) = &by_hash[path.last().unwrap()];
+                if header.prev_block_hash == [0; 32] {
+                    break Some(BigUint::zero());
+                }
+                if let Some(work) = chain_work.get(&header.prev_block_hash) {
+                    break Some(work.clone());
+                }
+                if !by_hash.contains_key(&header.prev_block_hash) {
+                    break None;
+                }
+                path.push(header.prev_block_hash);
+            };
+            let Some(mut work) = parent_work else {
+                continue;
+            };
+            while let Some(hash) = path.pop() {
+                work += block_work(by_hash[&hash].1.bits);
+                chain_work.insert(hash, work.clone());
+            }
+        }
+
+        // Like Bitcoin Core, the first block read wins a tie.
+        let Some((mut tip, _)) = chain_work.iter().max_by(|(a, a_work), (b, b_work)| {
+            a_work.cmp(b_work).then(by_hash[*b].0.cmp(&by_hash[*a].0))
+        }) else {
+            return Ok(vec![]);
+        };
+        let mut chain = Vec::new();
+        loop {
+            let (_, header) = by_hash[tip].clone();
+            chain.push(header);
+            match chain_work.get_key_value(&chain.last().unwrap().prev_block_hash) {
+                Some((prev, _)) => tip = prev,
+                None => break,
+            }
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+}
+
+/// Expected number of hashes to find a block with compact target `bits`,
+/// `2^256 / (target + 1)`. Negative, zero and overflowing targets count as no
+/// work, as in Bitcoin Core.
+fn block_work(bits: u32) -> BigUint {
+    let exponent = bits >> 24;
+    let mantissa = bits & 0x007f_ffff;
+    if bits & 0x0080_0000 != 0 {
+        return BigUint::zero();
+    }
+    let target = if exponent <= 3 {
+        BigUint::from(mantissa >> (8 * (3 - exponent)))
+    } else {
+        BigUint::from(mantissa) << (8 * (exponent - 3))
+    };
+    if target.is_zero() || target.bits() > 256 {
+        return BigUint::zero();
+    }
+    (BigUint::one() << 256) / (target + 1u32)
+}
+
+impl HeaderSource for BlockFiles {
+    fn read_headers(&mut self, start: usize, count: usize) -> Result<Vec<CircuitBlockHeader>> {
+        Ok(self
+            .best_chain()?
+            .into_iter()
+            .skip(start)
+            .take(count)
+            .collect())
+    }
+}
+
+/// Bitcoin Core JSON-RPC client reading headers with `getblockhash` and
+/// `getblockheader`.
+pub struct RpcClient {
+    url: String,
+    auth: Option<String>,
+}
+
+impl RpcClient {
+    pub fn new(url: String, user: Option<String>, password: Option<String>) -> Self {
+        let auth = user.map(|user| {
+            let credentials = format!("{}:{}", user, password.unwrap_or_default());
+            format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            )
+        });
+        Self { url, auth }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let mut request = ureq::post(&self.url);
+        if let Some(auth) = &self.auth {
+            request = request.set("Authorization", auth);
+        }
+        let response: Value = request
+            .send_json(json!({
+                "jsonrpc": "1.0",
+                "id": "winternitz-host",
+                "method": method,
+                "params": params,
+            }))?
+            .into_json()?;
+        if !response["error"].is_null() {
+            return Err(format!("{} failed: {}", method, response["error"]).into());
+        }
+        Ok(response["result"].clone())
+    }
+
+    fn block_header(&self, height: usize) -> Result<CircuitBlockHeader> {
+        let hash = self.call("getblockhash", json!([height]))?;
+        let header = self.call("getblockheader", json!([hash, false]))?;
+        let header = hex::decode(header.as_str().ok_or("Header is not a hex string")?)?;
+        Ok(CircuitBlockHeader::try_from_slice(&header)?)
+    }
+}
+
+impl HeaderSource for RpcClient {
+    fn read_headers(&mut self, start: usize, count: usize) -> Result<Vec<CircuitBlockHeader>> {
+        let block_count = self
+            .call("getblockcount", json!([]))?
+            .as_u64()
+            .ok_or("Block count is not a number")? as usize;
+        let end = (block_count + 1).min(start.saturating_add(count));
+        (start..end)
+            .map(|height| self.block_header(height))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    const HEADERS: &[u8] = include_bytes!("regtest-headers.bin");
+
+    fn regtest_headers(count: usize) -> Vec<CircuitBlockHeader> {
+        parse_headers(&HEADERS[..count * HEADER_SIZE]).unwrap()
+    }
+
+    /// Serves the regtest headers over JSON-RPC, one request per connection.
+    fn mock_rpc_server(headers: Vec<CircuitBlockHeader>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+
+                let result = match request["method"].as_str().unwrap() {
+                    "getblockcount" => json!(headers.len() - 1),
+                    "getblockhash" => {
+                        let height = request["params"][0].as_u64().unwrap() as usize;
+                        json!(hex::encode(headers[height].compute_block_hash()))
+                    }
+                    "getblockheader" => {
+                        let hash = request["params"][0].as_str().unwrap();
+                        let header = headers
+                            .iter()
+                            .find(|header| hex::encode(header.compute_block_hash()) == hash)
+                            .unwrap();
+                        json!(hex::encode(borsh::to_vec(header).unwrap()))
+                    }
+                    method => panic!("Unexpected method {}", method),
+                };
+                let body =
+                    json!({ "result": result, "error": null, "id": request["id"] }).to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_check_linked() {
+        let mut headers = regtest_headers(3);
+        assert!(check_linked(&headers).is_ok());
+
+        headers.swap(1, 2);
+        assert!(check_linked(&headers).is_err());
+    }
+
+    #[test]
+    fn test_rpc_client_reads_headers() {
+        let headers = regtest_headers(4);
+        let mut client = RpcClient::new(
+            mock_rpc_server(headers.clone()),
+            Some("user".to_string()),
+            Some("password".to_string()),
+        );
+
+        assert_eq!(client.headers(0, usize::MAX).unwrap(), headers);
+        assert_eq!(client.headers(1, 2).unwrap(), headers[1..3]);
+    }
+
+    const REGTEST_MAGIC: [u8; 4] = [0xfa, 0xbf, 0xb5, 0xda];
+
+    /// Writes `records` of `(size, header)` as `blk00000.dat` in a fresh directory,
+    /// followed by preallocated space.
+    fn block_files(name: &str, records: &[(u32, &CircuitBlockHeader)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blk-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut file = Vec::new();
+        for (size, header) in records {
+            file.extend_from_slice(&REGTEST_MAGIC);
+            file.extend_from_slice(&size.to_le_bytes());
+            let mut block = borsh::to_vec(header).unwrap();
+            block.resize(*size as usize, 0);
+            file.extend_from_slice(&block);
+        }
+        file.extend_from_slice(&[0; 128]);
+        fs::write(dir.join("blk00000.dat"), file).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_block_files_rebuild_best_chain() {
+        let headers = regtest_headers(3);
+        // Out of order, as blocks can arrive, with a transaction-sized record.
+        let dir = block_files(
+            "order",
+            &[(80, &headers[2]), (80, &headers[0]), (120, &headers[1])],
+        );
+
+        let mut block_files = BlockFiles::new(dir.clone(), REGTEST_MAGIC);
+        assert_eq!(block_files.headers(0, usize::MAX).unwrap(), headers);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_block_files_follow_most_work() {
+        let headers = regtest_headers(3);
+        // One block on genesis at mainnet difficulty outweighs two regtest blocks.
+        let mut fork = headers[1].clone();
+        fork.bits = 0x1d00ffff;
+        let dir = block_files(
+            "work",
+            &[
+                (80, &headers[0]),
+                (80, &headers[1]),
+                (80, &headers[2]),
+                (80, &fork),
+            ],
+        );
+
+        let mut block_files = BlockFiles::new(dir.clone(), REGTEST_MAGIC);
+        assert_eq!(
+            block_files.headers(0, usize::MAX).unwrap(),
+            vec![headers[0].clone(), fork]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_block_files_reject_bad_sizes() {
+        let headers = regtest_headers(2);
+        for (name, size) in [("short", 79), ("long", 1 << 20)] {
+            let dir = block_files(name, &[(80, &headers[0]), (80, &headers[1])]);
+            let path = dir.join("blk00000.dat");
+            let mut file = fs::read(&path).unwrap();
+            file[4 + 88..8 + 88].copy_from_slice(&(size as u32).to_le_bytes());
+            fs::write(&path, file).unwrap();
+
+            let mut block_files = BlockFiles::new(dir.clone(), REGTEST_MAGIC);
+            let error = block_files.headers(0, usize::MAX).unwrap_err();
+            assert!(error.to_string().starts_with("Bad block size"));
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_block_work() {
+        assert_eq!(block_work(0x207fffff), BigUint::from(2u32));
+        assert_eq!(block_work(0x1d00ffff), BigUint::from(0x0001_0001_0001u64));
+        assert!(block_work(0x1d80ffff).is_zero());
+        assert!(block_work(0xff7fffff).is_zero());
+    }
+}
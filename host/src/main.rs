@@ -1,131 +1,424 @@
-use borsh::{self, BorshDeserialize};
-use header_chain::header_chain::{
-    BlockHeaderCircuitOutput, CircuitBlockHeader, HeaderChainCircuitInput, HeaderChainPrevProofType,
-};
-use rand::{rngs::SmallRng, Rng, SeedableRng};
-use risc0_zkvm::{
-    compute_image_id, default_executor, default_prover, ExecutorEnv, ProverOpts, Receipt,
-};
-use std::convert::TryInto;
-use winternitz_core::groth16::CircuitGroth16Proof;
-use winternitz_core::winternitz::{
-    generate_public_key, sign_digits, Parameters, WinternitzCircuitInput,
-};
-use winternitz_core::WorkOnlyCircuitInput;
-
-const HEADERS: &[u8] = include_bytes!("regtest-headers.bin");
-const HEADERCHAIN_ELF: &[u8] = include_bytes!("../../elfs/regtest-headerchain-guest");
-const WINTERNITZ_ELF: &[u8] = include_bytes!("../../elfs/regtest-winternitz-guest");
-const WORK_ONLY_ELF: &[u8] = include_bytes!("../../elfs/regtest-work-only-guest");
-
-fn main() {
-    let headerchain_id: [u32; 8] = compute_image_id(HEADERCHAIN_ELF).unwrap().into();
-    let winternitz_id: [u32; 8] = compute_image_id(WINTERNITZ_ELF).unwrap().into();
-    let work_only_id: [u32; 8] = compute_image_id(WORK_ONLY_ELF).unwrap().into();
-
-    println!("HEADERCHAIN_ID: {:?}", headerchain_id);
-    println!("WINTERNITZ_ID: {:?}", winternitz_id);
-    println!("WORK_ONLY_ID: {:?}", work_only_id);
-
-    let headerchain_proof: Receipt = generate_header_chain_proof();
-    let block_header_circuit_output: BlockHeaderCircuitOutput =
-        borsh::BorshDeserialize::try_from_slice(&headerchain_proof.journal.bytes[..]).unwrap();
-    let work_only_circuit_input: WorkOnlyCircuitInput = WorkOnlyCircuitInput {
-        header_chain_circuit_output: block_header_circuit_output,
-        method_id: headerchain_id,
-    };
-    let work_only_groth16_proof_receipt: Receipt =
-        call_work_only(headerchain_proof, &work_only_circuit_input);
-
-    let g16_proof_receipt: &risc0_zkvm::Groth16Receipt<risc0_zkvm::ReceiptClaim> =
-        work_only_groth16_proof_receipt.inner.groth16().unwrap();
-    println!("G16 PROOF RECEIPT: {:?}", g16_proof_receipt);
-
-    let seal =
-        CircuitGroth16Proof::from_seal(g16_proof_receipt.seal.as_slice().try_into().unwrap());
-
-    let compressed_proof = seal.to_compressed().unwrap();
-
-    let commited_total_work: [u8; 16] = work_only_groth16_proof_receipt
-        .journal
-        .bytes
-        .try_into()
-        .unwrap();
-
-    let mut compressed_proof_and_total_work: Vec<u8> = vec![0; 144];
-    compressed_proof_and_total_work[0..128].copy_from_slice(&compressed_proof);
-    compressed_proof_and_total_work[128..144].copy_from_slice(&commited_total_work);
-
-    let n0 = compressed_proof_and_total_work.len();
-    let log_d = 8;
-    let params = Parameters::new(n0.try_into().unwrap(), log_d);
-    let input: u64 = 1;
-    let mut rng = SmallRng::seed_from_u64(input);
-    let secret_key: Vec<u8> = (0..n0).map(|_| rng.gen()).collect();
-    let pub_key: Vec<[u8; 20]> = generate_public_key(&params, &secret_key);
-    let signature = sign_digits(&params, &secret_key, &compressed_proof_and_total_work);
-
-    let winternitz_circuit_input: WinternitzCircuitInput = WinternitzCircuitInput {
-        pub_key,
-        params,
-        signature,
-        message: compressed_proof_and_total_work,
-    };
-
-    let mut binding = ExecutorEnv::builder();
-    let env = binding.write_slice(&borsh::to_vec(&winternitz_circuit_input).unwrap());
-    let env = env.build().unwrap();
-    let executor = default_executor();
-
-    let _ = executor.execute(env, WINTERNITZ_ELF);
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use headers::{BlockFiles, HeaderSource, RawHeaderFile, RpcClient};
+use std::path::PathBuf;
+use store::ArtifactStore;
+use winternitz_core::groth16::CompressedSealEncoding;
+use winternitz_core::registry::network_magic;
+
+mod commands;
+mod headers;
+mod manifest;
+mod store;
+
+#[derive(Parser)]
+#[command(
+    about = "Prove and verify the header-chain, work-only, fork-choice and winternitz circuits"
+)]
+struct Cli {
+    /// Network to prove and sign for, selecting the guest ELFs built for it.
+    #[arg(long, global = true, value_enum, default_value_t = Network::Regtest)]
+    network: Network,
+    /// Directory holding the `<network>-<circuit>-guest` ELFs. ELFs embedded with
+    /// the `embed-elfs` feature are used when a file is missing.
+    #[arg(long, global = true, default_value = "elfs")]
+    elfs_dir: PathBuf,
+    /// Prove the header-chain and work-only guests with fake receipts and sign
+    /// with the test Groth16 setup, for pipelines without a real prover. Uses the
+    /// `<network>-winternitz-dev-guest` ELF built with `WINTERNITZ_DEV_MODE=1`.
+    /// Only in hosts built with the `dev` feature.
+    #[cfg(feature = "dev")]
+    #[arg(long, global = true)]
+    dev: bool,
+    /// Artifact store to save receipts and signing artifacts in. Proving commands
+    /// reuse receipts already in the store.
+    #[arg(long, global = true)]
+    store: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the image IDs of the guest ELFs.
+    ImageIds {
+        /// Record the image IDs in this manifest, usually `elfs/image-ids.json`.
+        /// The host checks the ELFs against the manifest it was built with.
+        #[arg(long)]
+        write_manifest: Option<PathBuf>,
+    },
+    /// Prove a batch of headers on top of genesis or a previous header-chain proof.
+    ProveHeaderchain {
+        #[command(flatten)]
+        source: HeaderSourceArgs,
+        /// Height of the first header to prove.
+        #[arg(long, default_value_t = 0)]
+        start: usize,
+        /// Number of headers to prove. Defaults to all headers up to the tip.
+        #[arg(long)]
+        count: Option<usize>,
+        #[arg(long)]
+        prev_proof: Option<PathBuf>,
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Prove all headers in batches, chaining each receipt into the next batch.
+    /// Resumes from the latest receipt in `receipts_dir`.
+    ProveHeaderchainIncremental {
+        #[command(flatten)]
+        source: HeaderSourceArgs,
+        #[arg(long, default_value_t = 100)]
+        batch_size: usize,
+        #[arg(long)]
+        receipts_dir: PathBuf,
+    },
+    /// Wrap a header-chain proof into a Groth16 work-only proof.
+    ProveWorkOnly {
+        #[arg(long)]
+        headerchain_proof: PathBuf,
+        /// `work_only_policy` JSON artifact. Defaults to accepting any chain.
+        #[arg(long)]
+        policy: Option<PathBuf>,
+        /// Raw headers on top of the policy's confirmed block, up to the best block.
+        #[arg(long)]
+        confirmation_headers: Option<PathBuf>,
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Prove which of two header-chain proofs from the same genesis state has more
+    /// work.
+    ProveForkChoice {
+        #[arg(long)]
+        first_proof: PathBuf,
+        #[arg(long)]
+        second_proof: PathBuf,
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Generate a Winternitz key pair for signing work-only proofs.
+    Keygen {
+        #[arg(long, default_value_t = 8)]
+        log_d: u32,
+        #[arg(long)]
+        secret_key: PathBuf,
+        #[arg(long)]
+        public_key: PathBuf,
+        /// Replace existing key files.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Sign a work-only proof and write the winternitz guest input.
+    Sign {
+        #[arg(long)]
+        work_only_proof: PathBuf,
+        #[arg(long)]
+        secret_key: PathBuf,
+        #[arg(long, value_enum, default_value_t = SealEncoding::Ark)]
+        seal_encoding: SealEncoding,
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Check the Winternitz signature of a winternitz guest input.
+    VerifySignature {
+        #[arg(long)]
+        input: PathBuf,
+    },
+    /// Execute the winternitz guest and print its cycle report. Several inputs are
+    /// verified as one batch.
+    ExecuteWinternitz {
+        #[arg(long, required = true)]
+        input: Vec<PathBuf>,
+    },
+    /// Prove the winternitz guest. Several inputs are verified as one batch.
+    ProveWinternitz {
+        #[arg(long, required = true)]
+        input: Vec<PathBuf>,
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Verify a receipt against the image ID of a circuit and print its journal.
+    VerifyReceipt {
+        #[arg(long, value_enum)]
+        circuit: Circuit,
+        #[arg(long)]
+        receipt: PathBuf,
+    },
+    /// Write an artifact as versioned JSON, see `winternitz_core::json`.
+    Export {
+        #[arg(long, value_enum)]
+        from: ExportSource,
+        #[arg(long)]
+        input: PathBuf,
+        /// Defaults to stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Convert a JSON artifact back to the file the other commands read: borsh
+    /// keys and winternitz inputs, raw seals.
+    Import {
+        #[arg(long)]
+        input: PathBuf,
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Inspect and maintain the artifact store given with `--store`.
+    #[command(subcommand)]
+    Store(StoreCommand),
+}
+
+#[derive(Subcommand)]
+enum StoreCommand {
+    /// List the stored entries and their artifacts.
+    List,
+    /// Check every artifact against its digest.
+    Verify,
+    /// Remove corrupted artifacts, entries without a receipt and entries of image
+    /// IDs that neither the manifest, the generated image ID tables nor, with
+    /// `--dev`, a dev winternitz ELF has.
+    Gc {
+        /// Only report what would be removed.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Where to read headers from. Exactly one of `--headers`, `--blocks-dir` and
+/// `--rpc-url` must be given.
+#[derive(Args)]
+struct HeaderSourceArgs {
+    /// Raw 80-byte block headers from genesis, concatenated.
+    #[arg(long)]
+    headers: Option<PathBuf>,
+    /// Bitcoin Core `blocks` directory holding `blk*.dat` files.
+    #[arg(long)]
+    blocks_dir: Option<PathBuf>,
+    /// Bitcoin Core JSON-RPC endpoint.
+    #[arg(long)]
+    rpc_url: Option<String>,
+    #[arg(long, requires = "rpc_url")]
+    rpc_user: Option<String>,
+    #[arg(long, requires = "rpc_url")]
+    rpc_password: Option<String>,
+}
+
+impl HeaderSourceArgs {
+    fn into_source(
+        self,
+        network: Network,
+    ) -> Result<Box<dyn HeaderSource>, Box<dyn std::error::Error>> {
+        match (self.headers, self.blocks_dir, self.rpc_url) {
+            (Some(path), None, None) => Ok(Box::new(RawHeaderFile::new(path))),
+            (None, Some(blocks_dir), None) => {
+                Ok(Box::new(BlockFiles::new(blocks_dir, network.magic())))
+            }
+            (None, None, Some(url)) => Ok(Box::new(RpcClient::new(
+                url,
+                self.rpc_user,
+                self.rpc_password,
+            ))),
+            _ => Err("Pass exactly one of --headers, --blocks-dir and --rpc-url".into()),
+        }
+    }
 }
 
-fn call_work_only(receipt: Receipt, input: &WorkOnlyCircuitInput) -> Receipt {
-    let mut binding = ExecutorEnv::builder();
-    binding.add_assumption(receipt);
-    let env = binding.write_slice(&borsh::to_vec(&input).unwrap());
-    let env = env.build().unwrap();
-    let prover = default_prover();
-    let receipt = prover
-        .prove_with_opts(env, WORK_ONLY_ELF, &ProverOpts::groth16())
-        .unwrap()
-        .receipt;
-    return receipt;
+/// Files the `export` command reads.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportSource {
+    /// Public key written by `keygen`.
+    PublicKey,
+    /// Secret key written by `keygen`.
+    SecretKey,
+    /// Winternitz guest input written by `sign`.
+    WinternitzInput,
+    /// Compressed seal signed in a winternitz guest input.
+    CompressedSeal,
+    /// Groth16 seal of a work-only receipt.
+    Seal,
+    /// Journal of a work-only, fork-choice or winternitz receipt.
+    Receipt,
 }
 
-fn generate_header_chain_proof() -> Receipt {
-    let header_chain_guest_id: [u32; 8] = compute_image_id(HEADERCHAIN_ELF)
-        .unwrap()
-        .as_words()
-        .try_into()
-        .unwrap();
+#[derive(Clone, Copy, ValueEnum)]
+enum SealEncoding {
+    Ark,
+    Bitvm,
+}
+
+impl From<SealEncoding> for CompressedSealEncoding {
+    fn from(encoding: SealEncoding) -> Self {
+        match encoding {
+            SealEncoding::Ark => CompressedSealEncoding::Ark,
+            SealEncoding::Bitvm => CompressedSealEncoding::BitVm,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum Network {
+    Mainnet,
+    Testnet4,
+    Signet,
+    Regtest,
+}
 
-    let batch_size: usize = 1;
+impl Network {
+    pub const ALL: [Network; 4] = [
+        Network::Mainnet,
+        Network::Testnet4,
+        Network::Signet,
+        Network::Regtest,
+    ];
 
-    let headers = HEADERS
-        .chunks(80)
-        .map(|header| CircuitBlockHeader::try_from_slice(header).unwrap())
-        .collect::<Vec<CircuitBlockHeader>>();
+    pub fn name(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet4 => "testnet4",
+            Network::Signet => "signet",
+            Network::Regtest => "regtest",
+        }
+    }
 
-    let start = 0;
-    let prev_proof = HeaderChainPrevProofType::GenesisBlock;
+    pub fn magic(&self) -> [u8; 4] {
+        network_magic(self.name()).expect("Every network has a magic")
+    }
 
-    let input = HeaderChainCircuitInput {
-        method_id: header_chain_guest_id,
-        prev_proof,
-        block_headers: headers[start..start + batch_size].to_vec(),
-    };
+    /// Network whose P2P magic is `magic`.
+    pub fn from_magic(magic: [u8; 4]) -> Option<Network> {
+        Network::ALL
+            .into_iter()
+            .find(|network| network.magic() == magic)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Circuit {
+    Headerchain,
+    WorkOnly,
+    Winternitz,
+    ForkChoice,
+}
 
-    let mut binding = ExecutorEnv::builder();
-    let env = binding.write_slice(&borsh::to_vec(&input).unwrap());
-    let env = env.build().unwrap();
+impl Circuit {
+    pub const ALL: [Circuit; 4] = [
+        Circuit::Headerchain,
+        Circuit::WorkOnly,
+        Circuit::Winternitz,
+        Circuit::ForkChoice,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Circuit::Headerchain => "headerchain",
+            Circuit::WorkOnly => "work-only",
+            Circuit::Winternitz => "winternitz",
+            Circuit::ForkChoice => "fork-choice",
+        }
+    }
+}
 
-    let prover = default_prover();
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    #[cfg(feature = "dev")]
+    let dev = cli.dev;
+    #[cfg(not(feature = "dev"))]
+    let dev = false;
+    let elfs = commands::Elfs::new(cli.elfs_dir, cli.network, dev);
+    let store = cli.store.map(ArtifactStore::open).transpose()?;
 
-    let receipt = prover
-        .prove_with_opts(env, HEADERCHAIN_ELF, &ProverOpts::succinct())
-        .unwrap()
-        .receipt;
+    // Listing image IDs is how a stale manifest gets fixed, so it runs unchecked.
+    if !matches!(cli.command, Command::ImageIds { .. }) {
+        commands::check_image_ids(&elfs)?;
+    }
 
-    return receipt;
+    match cli.command {
+        Command::ImageIds { write_manifest } => {
+            commands::image_ids(&elfs, write_manifest.as_deref())
+        }
+        Command::ProveHeaderchain {
+            source,
+            start,
+            count,
+            prev_proof,
+            output,
+        } => commands::prove_headerchain(
+            &elfs,
+            store.as_ref(),
+            source.into_source(cli.network)?.as_mut(),
+            start,
+            count.unwrap_or(usize::MAX),
+            prev_proof.as_deref(),
+            &output,
+        ),
+        Command::ProveHeaderchainIncremental {
+            source,
+            batch_size,
+            receipts_dir,
+        } => commands::prove_headerchain_incremental(
+            &elfs,
+            store.as_ref(),
+            source.into_source(cli.network)?.as_mut(),
+            batch_size,
+            &receipts_dir,
+        ),
+        Command::ProveWorkOnly {
+            headerchain_proof,
+            policy,
+            confirmation_headers,
+            output,
+        } => commands::prove_work_only(
+            &elfs,
+            store.as_ref(),
+            &headerchain_proof,
+            policy.as_deref(),
+            confirmation_headers.as_deref(),
+            &output,
+        ),
+        Command::ProveForkChoice {
+            first_proof,
+            second_proof,
+            output,
+        } => {
+            commands::prove_fork_choice(&elfs, store.as_ref(), &first_proof, &second_proof, &output)
+        }
+        Command::Keygen {
+            log_d,
+            secret_key,
+            public_key,
+            force,
+        } => commands::keygen(log_d, &secret_key, &public_key, force),
+        Command::Sign {
+            work_only_proof,
+            secret_key,
+            seal_encoding,
+            output,
+        } => commands::sign(
+            &elfs,
+            store.as_ref(),
+            &work_only_proof,
+            &secret_key,
+            seal_encoding.into(),
+            &output,
+        ),
+        Command::VerifySignature { input } => commands::verify_signature(&input),
+        Command::ExecuteWinternitz { input } => commands::execute_winternitz(&elfs, &input),
+        Command::ProveWinternitz { input, output } => {
+            commands::prove_winternitz(&elfs, store.as_ref(), &input, &output)
+        }
+        Command::VerifyReceipt { circuit, receipt } => {
+            commands::verify_receipt(&elfs, circuit, &receipt)
+        }
+        Command::Export {
+            from,
+            input,
+            output,
+        } => commands::export(from, &input, output.as_deref()),
+        Command::Import { input, output } => commands::import(&input, &output),
+        Command::Store(command) => {
+            let store = store.as_ref().ok_or("--store is required")?;
+            match command {
+                StoreCommand::List => commands::store_list(store),
+                StoreCommand::Verify => commands::store_verify(store),
+                StoreCommand::Gc { dry_run } => commands::store_gc(&elfs, store, dry_run),
+            }
+        }
+    }
 }
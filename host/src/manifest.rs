@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use risc0_zkvm::sha::Digest;
+use winternitz_core::registry::work_only_method_id;
+
+use crate::{Circuit, Network};
+
+/// Expected image IDs, checked in next to the ELFs and compiled into the host.
+const EMBEDDED_MANIFEST: &str = include_str!("../../elfs/image-ids.json");
+
+#[derive(Debug, PartialEq)]
+pub enum ManifestError {
+    Malformed(String),
+    Missing {
+        network: &'static str,
+        circuit: &'static str,
+    },
+    Mismatch {
+        network: &'static str,
+        circuit: &'static str,
+        expected: String,
+        actual: String,
+    },
+    /// The winternitz guest verifies work-only proofs of another image.
+    WorkOnlyIdNotPinned {
+        network: &'static str,
+        work_only_id: String,
+    },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Malformed(e) => write!(f, "Malformed image ID manifest: {}", e),
+            ManifestError::Missing { network, circuit } => write!(
+                f,
+                "No expected image ID for the {} {} guest, run `image-ids --write-manifest` after rebuilding the ELFs",
+                network, circuit
+            ),
+            ManifestError::Mismatch {
+                network,
+                circuit,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Image ID of the {} {} guest is {}, the manifest expects {}",
+                network, circuit, actual, expected
+            ),
+            ManifestError::WorkOnlyIdNotPinned {
+                network,
+                work_only_id,
+            } => write!(
+                f,
+                "The {} winternitz guest does not verify the work-only image {}, rebuild it after the work-only guest",
+                network, work_only_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+/// Image IDs per network and circuit, as hex encoded digest bytes.
+#[derive(Debug, Default, PartialEq)]
+pub struct Manifest(BTreeMap<String, BTreeMap<String, String>>);
+
+impl Manifest {
+    pub fn embedded() -> Result<Self, ManifestError> {
+        Self::parse(EMBEDDED_MANIFEST)
+    }
+
+    pub fn parse(json: &str) -> Result<Self, ManifestError> {
+        serde_json::from_str(json)
+            .map(Manifest)
+            .map_err(|e| ManifestError::Malformed(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.0).unwrap() + "\n"
+    }
+
+    pub fn set(&mut self, network: Network, circuit: Circuit, image_id: [u8; 32]) {
+        self.0
+            .entry(network.name().to_string())
+            .or_default()
+            .insert(circuit.name().to_string(), hex::encode(image_id));
+    }
+
+    /// Expected image ID of a guest, as digest bytes.
+    pub fn image_id(&self, network: Network, circuit: Circuit) -> Result<[u8; 32], ManifestError> {
+        let missing = ManifestError::Missing {
+            network: network.name(),
+            circuit: circuit.name(),
+        };
+        let expected = self
+            .0
+            .get(network.name())
+            .and_then(|circuits| circuits.get(circuit.name()))
+            .ok_or(missing)?;
+        let mut image_id = [0u8; 32];
+        hex::decode_to_slice(expected, &mut image_id).map_err(|e| {
+            ManifestError::Malformed(format!("{} {}: {}", network.name(), circuit.name(), e))
+        })?;
+        Ok(image_id)
+    }
+
+    pub fn check(
+        &self,
+        network: Network,
+        circuit: Circuit,
+        image_id: [u8; 32],
+    ) -> Result<(), ManifestError> {
+        let expected = self.image_id(network, circuit)?;
+        if expected != image_id {
+            return Err(ManifestError::Mismatch {
+                network: network.name(),
+                circuit: circuit.name(),
+                expected: hex::encode(expected),
+                actual: hex::encode(image_id),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the winternitz guest of `network` verifies proofs of
+/// `work_only_id`. The guest derives the work-only image ID for its claim digest
+/// from the table the core crate generates from `elfs/`, so the check reads the
+/// same table.
+pub fn check_work_only_pinned(
+    network: Network,
+    work_only_id: [u32; 8],
+) -> Result<(), ManifestError> {
+    if work_only_method_id(network.name()) == Some(work_only_id) {
+        return Ok(());
+    }
+    Err(ManifestError::WorkOnlyIdNotPinned {
+        network: network.name(),
+        work_only_id: hex::encode(Digest::from(work_only_id)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_zkvm::compute_image_id;
+    use std::path::Path;
+    use winternitz_core::registry::WORK_ONLY_METHOD_IDS;
+
+    #[test]
+    fn test_embedded_manifest_parses() {
+        Manifest::embedded().unwrap();
+    }
+
+    /// The manifest lists exactly the release ELFs in `elfs/`, with their image IDs.
+    /// The core crate derives the pinned header-chain and work-only IDs from the
+    /// same ELFs, so this also keeps them in line with the manifest.
+    #[test]
+    fn test_manifest_matches_elfs() {
+        let manifest = Manifest::embedded().unwrap();
+        let elfs_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../elfs");
+        for network in Network::ALL {
+            for circuit in Circuit::ALL {
+                let file_name = format!("{}-{}-guest", network.name(), circuit.name());
+                let Ok(elf) = std::fs::read(elfs_dir.join(&file_name)) else {
+                    assert!(
+                        manifest.image_id(network, circuit).is_err(),
+                        "{} is in the manifest but not in elfs/",
+                        file_name
+                    );
+                    continue;
+                };
+                let image_id = compute_image_id(&elf).unwrap();
+                manifest
+                    .check(network, circuit, image_id.as_bytes().try_into().unwrap())
+                    .unwrap_or_else(|e| panic!("{}", e));
+            }
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let mut manifest = Manifest::default();
+        manifest.set(Network::Regtest, Circuit::WorkOnly, [1; 32]);
+        let manifest = Manifest::parse(&manifest.to_json()).unwrap();
+
+        assert!(manifest
+            .check(Network::Regtest, Circuit::WorkOnly, [1; 32])
+            .is_ok());
+        assert!(matches!(
+            manifest.check(Network::Regtest, Circuit::WorkOnly, [2; 32]),
+            Err(ManifestError::Mismatch { .. })
+        ));
+        assert!(matches!(
+            manifest.check(Network::Signet, Circuit::WorkOnly, [1; 32]),
+            Err(ManifestError::Missing { .. })
+        ));
+        assert_eq!(
+            manifest.image_id(Network::Regtest, Circuit::WorkOnly),
+            Ok([1; 32])
+        );
+
+        let manifest = Manifest::parse(r#"{"regtest": {"work-only": "01"}}"#).unwrap();
+        assert!(matches!(
+            manifest.image_id(Network::Regtest, Circuit::WorkOnly),
+            Err(ManifestError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_work_only_pinned() {
+        let (network, work_only_id) = WORK_ONLY_METHOD_IDS[0];
+        let network = Network::ALL
+            .into_iter()
+            .find(|n| n.name() == network)
+            .unwrap();
+        assert!(check_work_only_pinned(network, work_only_id).is_ok());
+        assert!(matches!(
+            check_work_only_pinned(network, [0; 8]),
+            Err(ManifestError::WorkOnlyIdNotPinned { .. })
+        ));
+    }
+}
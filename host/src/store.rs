@@ -0,0 +1,430 @@
+use risc0_zkvm::Receipt;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const INPUT_INDEX_DIR: &str = "by-input";
+const TMP_EXTENSION: &str = "tmp";
+
+/// Identifies the artifacts of one proof: the image ID of the circuit and the
+/// SHA-256 digest of its journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArtifactKey {
+    pub image_id: [u32; 8],
+    pub journal_digest: [u8; 32],
+}
+
+impl ArtifactKey {
+    pub fn new(image_id: [u32; 8], journal: &[u8]) -> Self {
+        Self {
+            image_id,
+            journal_digest: Sha256::digest(journal).into(),
+        }
+    }
+
+    fn image_dir_name(image_id: &[u32; 8]) -> String {
+        image_id
+            .iter()
+            .map(|word| format!("{:08x}", word))
+            .collect()
+    }
+
+    fn from_dir_names(image_dir: &str, journal_dir: &str) -> Option<Self> {
+        if image_dir.len() != 64 {
+            return None;
+        }
+        let mut image_id = [0u32; 8];
+        for (i, word) in image_id.iter_mut().enumerate() {
+            *word = u32::from_str_radix(image_dir.get(i * 8..i * 8 + 8)?, 16).ok()?;
+        }
+        let journal_digest = hex::decode(journal_dir).ok()?.try_into().ok()?;
+        Some(Self {
+            image_id,
+            journal_digest,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// Bincode encoded [`Receipt`].
+    Receipt,
+    /// Borsh encoded guest input the receipt was proven from.
+    Input,
+    CompressedSeal,
+    WinternitzPublicKey,
+    WinternitzSignature,
+}
+
+impl ArtifactKind {
+    pub const ALL: [ArtifactKind; 5] = [
+        ArtifactKind::Receipt,
+        ArtifactKind::Input,
+        ArtifactKind::CompressedSeal,
+        ArtifactKind::WinternitzPublicKey,
+        ArtifactKind::WinternitzSignature,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArtifactKind::Receipt => "receipt",
+            ArtifactKind::Input => "input",
+            ArtifactKind::CompressedSeal => "compressed-seal",
+            ArtifactKind::WinternitzPublicKey => "winternitz-public-key",
+            ArtifactKind::WinternitzSignature => "winternitz-signature",
+        }
+    }
+}
+
+/// Summary of [`ArtifactStore::gc`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GcReport {
+    pub removed_entries: usize,
+    pub removed_files: usize,
+}
+
+/// On-disk store of proofs and the artifacts derived from them.
+///
+/// Artifacts live in `<root>/<image id>/<journal digest>/<kind>.<sha256>`, so
+/// every file carries the digest it is checked against. Proven inputs are also
+/// indexed by their digest, which lets an interrupted pipeline skip the stages
+/// that already have a receipt.
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn open(root: PathBuf) -> Result<Self> {
+        fs::create_dir_all(root.join(INPUT_INDEX_DIR))?;
+        Ok(Self { root })
+    }
+
+    fn entry_dir(&self, key: &ArtifactKey) -> PathBuf {
+        self.root
+            .join(ArtifactKey::image_dir_name(&key.image_id))
+            .join(hex::encode(key.journal_digest))
+    }
+
+    fn input_index_path(&self, image_id: &[u32; 8], input: &[u8]) -> PathBuf {
+        let input_digest: [u8; 32] = Sha256::digest(input).into();
+        self.root.join(INPUT_INDEX_DIR).join(format!(
+            "{}-{}",
+            ArtifactKey::image_dir_name(image_id),
+            hex::encode(input_digest)
+        ))
+    }
+
+    /// Path of the stored artifact of `kind`, whatever its digest.
+    fn find(&self, key: &ArtifactKey, kind: ArtifactKind) -> Result<Option<PathBuf>> {
+        let dir = self.entry_dir(key);
+        if !dir.exists() {
+            return Ok(None);
+        }
+        let prefix = format!("{}.", kind.name());
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_kind = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix))
+                .is_some_and(|digest| digest.len() == 64);
+            if is_kind {
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Writes through a temporary file, so readers never see a partial artifact.
+    fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+        let tmp_path = path.with_extension(TMP_EXTENSION);
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Checks a file against the digest in its name.
+    fn is_intact(path: &Path, bytes: &[u8]) -> bool {
+        path.extension()
+            .and_then(|digest| digest.to_str())
+            .is_some_and(|digest| digest == hex::encode(Sha256::digest(bytes)))
+    }
+
+    pub fn put(&self, key: &ArtifactKey, kind: ArtifactKind, bytes: &[u8]) -> Result<()> {
+        let dir = self.entry_dir(key);
+        fs::create_dir_all(&dir)?;
+        if let Some(old) = self.find(key, kind)? {
+            fs::remove_file(old)?;
+        }
+        let path = dir.join(format!(
+            "{}.{}",
+            kind.name(),
+            hex::encode(Sha256::digest(bytes))
+        ));
+        Self::write_atomic(&path, bytes)
+    }
+
+    /// Reads an artifact, failing if it no longer matches its digest.
+    pub fn get(&self, key: &ArtifactKey, kind: ArtifactKind) -> Result<Option<Vec<u8>>> {
+        let Some(path) = self.find(key, kind)? else {
+            return Ok(None);
+        };
+        let bytes = fs::read(&path)?;
+        if !Self::is_intact(&path, &bytes) {
+            return Err(format!("{} is corrupted", path.display()).into());
+        }
+        Ok(Some(bytes))
+    }
+
+    /// Stores a receipt with the input it was proven from.
+    pub fn put_proof(
+        &self,
+        image_id: [u32; 8],
+        input: &[u8],
+        receipt: &Receipt,
+    ) -> Result<ArtifactKey> {
+        let key = ArtifactKey::new(image_id, &receipt.journal.bytes);
+        self.put(&key, ArtifactKind::Receipt, &bincode::serialize(receipt)?)?;
+        self.put(&key, ArtifactKind::Input, input)?;
+        Self::write_atomic(
+            &self.input_index_path(&image_id, input),
+            hex::encode(key.journal_digest).as_bytes(),
+        )?;
+        Ok(key)
+    }
+
+    pub fn get_receipt(&self, key: &ArtifactKey) -> Result<Option<Receipt>> {
+        match self.get(key, ArtifactKind::Receipt)? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the receipt previously proven from `input`, if any.
+    pub fn find_proof(&self, image_id: [u32; 8], input: &[u8]) -> Result<Option<Receipt>> {
+        let index_path = self.input_index_path(&image_id, input);
+        if !index_path.exists() {
+            return Ok(None);
+        }
+        let journal_digest = hex::decode(fs::read(index_path)?)?
+            .try_into()
+            .map_err(|_| "Malformed input index")?;
+        self.get_receipt(&ArtifactKey {
+            image_id,
+            journal_digest,
+        })
+    }
+
+    /// Every entry with the kinds of artifacts it holds.
+    pub fn list(&self) -> Result<Vec<(ArtifactKey, Vec<ArtifactKind>)>> {
+        let mut entries = Vec::new();
+        for image_entry in fs::read_dir(&self.root)? {
+            let image_path = image_entry?.path();
+            let Some(image_dir) = image_path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if image_dir == INPUT_INDEX_DIR || !image_path.is_dir() {
+                continue;
+            }
+            for journal_entry in fs::read_dir(&image_path)? {
+                let journal_path = journal_entry?.path();
+                let key = journal_path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|journal_dir| ArtifactKey::from_dir_names(image_dir, journal_dir));
+                let Some(key) = key else {
+                    continue;
+                };
+                let mut kinds = Vec::new();
+                for kind in ArtifactKind::ALL {
+                    if self.find(&key, kind)?.is_some() {
+                        kinds.push(kind);
+                    }
+                }
+                entries.push((key, kinds));
+            }
+        }
+        entries.sort_by_key(|(key, _)| (key.image_id, key.journal_digest));
+        Ok(entries)
+    }
+
+    /// Returns the artifacts that no longer match their digest, and receipts whose
+    /// journal does not match their key.
+    pub fn verify(&self) -> Result<Vec<(ArtifactKey, ArtifactKind)>> {
+        let mut corrupted = Vec::new();
+        for (key, kinds) in self.list()? {
+            for kind in kinds {
+                let intact = match kind {
+                    ArtifactKind::Receipt => {
+                        self.get_receipt(&key)
+                            .ok()
+                            .flatten()
+                            .is_some_and(|receipt| {
+                                ArtifactKey::new(key.image_id, &receipt.journal.bytes) == key
+                            })
+                    }
+                    _ => self.get(&key, kind).is_ok(),
+                };
+                if !intact {
+                    corrupted.push((key, kind));
+                }
+            }
+        }
+        Ok(corrupted)
+    }
+
+    /// Removes leftover temporary files, corrupted artifacts, entries without a
+    /// receipt and every entry of an image ID not in `live_image_ids`, e.g. of ELFs
+    /// that have since been rebuilt. With `dry_run` nothing is removed, the report
+    /// counts what would be.
+    pub fn gc(&self, live_image_ids: &[[u32; 8]], dry_run: bool) -> Result<GcReport> {
+        if live_image_ids.is_empty() {
+            return Err("No live image IDs, refusing to remove every entry".into());
+        }
+        let remove_file = |path: &Path| -> Result<()> {
+            if !dry_run {
+                fs::remove_file(path)?;
+            }
+            Ok(())
+        };
+        let mut report = GcReport::default();
+        let mut removed_keys = Vec::new();
+        let corrupted = self.verify()?;
+
+        for (key, kinds) in self.list()? {
+            let dir = self.entry_dir(&key);
+            let receipt_intact = kinds.contains(&ArtifactKind::Receipt)
+                && !corrupted.contains(&(key, ArtifactKind::Receipt));
+            if !live_image_ids.contains(&key.image_id) || !receipt_intact {
+                if !dry_run {
+                    fs::remove_dir_all(dir)?;
+                }
+                removed_keys.push(key);
+                report.removed_entries += 1;
+                continue;
+            }
+
+            for (_, kind) in corrupted.iter().filter(|(other, _)| *other == key) {
+                if let Some(path) = self.find(&key, *kind)? {
+                    remove_file(&path)?;
+                    report.removed_files += 1;
+                }
+            }
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == TMP_EXTENSION) {
+                    remove_file(&path)?;
+                    report.removed_files += 1;
+                }
+            }
+        }
+
+        // Drop index entries whose receipt is gone.
+        for entry in fs::read_dir(self.root.join(INPUT_INDEX_DIR))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == TMP_EXTENSION) {
+                remove_file(&path)?;
+                report.removed_files += 1;
+                continue;
+            }
+            let Some((image_dir, _)) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split_once('-'))
+            else {
+                continue;
+            };
+            let key = fs::read_to_string(&path)
+                .ok()
+                .and_then(|journal_dir| ArtifactKey::from_dir_names(image_dir, &journal_dir));
+            let live = match key {
+                Some(key) => {
+                    !removed_keys.contains(&key)
+                        && self.find(&key, ArtifactKind::Receipt)?.is_some()
+                }
+                None => false,
+            };
+            if !live {
+                remove_file(&path)?;
+                report.removed_files += 1;
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> (PathBuf, ArtifactStore) {
+        let root = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        (root.clone(), ArtifactStore::open(root).unwrap())
+    }
+
+    #[test]
+    fn test_put_get_and_verify() {
+        let (root, store) = temp_store("artifact-store");
+        let key = ArtifactKey::new([1; 8], b"journal");
+
+        store
+            .put(&key, ArtifactKind::CompressedSeal, b"seal")
+            .unwrap();
+        store
+            .put(&key, ArtifactKind::CompressedSeal, b"new seal")
+            .unwrap();
+        assert_eq!(
+            store.get(&key, ArtifactKind::CompressedSeal).unwrap(),
+            Some(b"new seal".to_vec())
+        );
+        assert_eq!(store.get(&key, ArtifactKind::Input).unwrap(), None);
+        assert_eq!(
+            store.list().unwrap(),
+            vec![(key, vec![ArtifactKind::CompressedSeal])]
+        );
+        assert!(store.verify().unwrap().is_empty());
+
+        let path = store
+            .find(&key, ArtifactKind::CompressedSeal)
+            .unwrap()
+            .unwrap();
+        fs::write(path, b"tampered").unwrap();
+        assert!(store.get(&key, ArtifactKind::CompressedSeal).is_err());
+        assert_eq!(
+            store.verify().unwrap(),
+            vec![(key, ArtifactKind::CompressedSeal)]
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_gc_removes_entries_without_receipt() {
+        let (root, store) = temp_store("artifact-store-gc");
+        let key = ArtifactKey::new([2; 8], b"journal");
+        store.put(&key, ArtifactKind::Input, b"input").unwrap();
+
+        let report = store.gc(&[[2; 8]], false).unwrap();
+        assert_eq!(report.removed_entries, 1);
+        assert!(store.list().unwrap().is_empty());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_gc_dry_run_and_empty_live_set() {
+        let (root, store) = temp_store("artifact-store-gc-dry-run");
+        let key = ArtifactKey::new([3; 8], b"journal");
+        store.put(&key, ArtifactKind::Input, b"input").unwrap();
+
+        assert!(store.gc(&[], false).is_err());
+        let report = store.gc(&[[3; 8]], true).unwrap();
+        assert_eq!(report.removed_entries, 1);
+        assert_eq!(
+            store.list().unwrap(),
+            vec![(key, vec![ArtifactKind::Input])]
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+//! Runs the guest ELFs in `elfs/` with the executor, chaining the header-chain,
+//! work-only and winternitz guests the way the host does. Header-chain receipts
+//! are replaced by unresolved assumptions, so nothing is proven; the winternitz
+//! pipeline runs the dev ELF on dev seals. Outcomes are checked through exit
+//! codes and journals only.
+
+use ark_std::UniformRand;
+use borsh::{BorshDeserialize, BorshSerialize};
+use header_chain::header_chain::{
+    BlockHeaderCircuitOutput, CircuitBlockHeader, HeaderChainCircuitInput, HeaderChainPrevProofType,
+};
+use risc0_zkvm::{
+    compute_image_id, default_executor, ExecutorEnv, ExitCode, ReceiptClaim, SessionInfo,
+};
+use winternitz_core::claim::work_only_public_inputs;
+use winternitz_core::constants::BITCOIN_NETWORK;
+use winternitz_core::dev::{dev_prove, DEV_VERIFIER_ENTRY};
+use winternitz_core::groth16::{CircuitGroth16Proof, CompressedSealEncoding};
+use winternitz_core::journal::{decode_journal, encode_journal};
+use winternitz_core::message::{encode_signed_message, SignedChainState};
+use winternitz_core::policy::WorkOnlyPolicy;
+use winternitz_core::registry::{latest_verifier_entry, Risc0VerifierEntry};
+use winternitz_core::winternitz::{
+    generate_public_key, pubkeys_digest, sign_digits, Parameters, WinternitzBatchCircuitOutput,
+    WinternitzCircuitInput, WinternitzCircuitOutput, WinternitzGuestInput,
+};
+use winternitz_core::zkvm::GuestExitCode;
+use winternitz_core::{WorkOnlyCircuitInput, WorkOnlyCircuitOutput};
+
+const HEADERS: &[u8] = include_bytes!("../src/regtest-headers.bin");
+const HEADER_COUNT: usize = 10;
+
+fn elf(circuit: &str) -> Vec<u8> {
+    let path = format!(
+        "{}/../elfs/{}-{}-guest",
+        env!("CARGO_MANIFEST_DIR"),
+        BITCOIN_NETWORK,
+        circuit
+    );
+    std::fs::read(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e))
+}
+
+fn execute_raw(
+    elf: &[u8],
+    input: &[u8],
+    assumptions: Vec<ReceiptClaim>,
+) -> anyhow::Result<SessionInfo> {
+    let mut builder = ExecutorEnv::builder();
+    for claim in assumptions {
+        builder.add_assumption(claim);
+    }
+    let env = builder.write_slice(input).build()?;
+    default_executor().execute(env, elf)
+}
+
+fn execute(
+    elf: &[u8],
+    input: &impl BorshSerialize,
+    assumptions: Vec<ReceiptClaim>,
+) -> anyhow::Result<SessionInfo> {
+    execute_raw(elf, &borsh::to_vec(input)?, assumptions)
+}
+
+fn assert_exit(session: anyhow::Result<SessionInfo>, code: GuestExitCode) {
+    assert_eq!(session.unwrap().exit_code, ExitCode::Halted(code as u32));
+}
+
+fn regtest_headers() -> Vec<CircuitBlockHeader> {
+    HEADERS
+        .chunks(80)
+        .take(HEADER_COUNT)
+        .map(|header| CircuitBlockHeader::try_from_slice(header).unwrap())
+        .collect()
+}
+
+/// Executes the header-chain guest on the first regtest headers and returns its
+/// image ID and journal.
+fn header_chain_output() -> ([u32; 8], Vec<u8>) {
+    let elf = elf("headerchain");
+    let method_id: [u32; 8] = compute_image_id(&elf).unwrap().into();
+    let input = HeaderChainCircuitInput {
+        method_id,
+        prev_proof: HeaderChainPrevProofType::GenesisBlock,
+        block_headers: regtest_headers(),
+    };
+    let session = execute(&elf, &input, vec![]).unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    (method_id, session.journal.bytes)
+}
+
+fn work_only_input(policy: WorkOnlyPolicy) -> (WorkOnlyCircuitInput, ReceiptClaim) {
+    let (method_id, journal) = header_chain_output();
+    let input = WorkOnlyCircuitInput {
+        header_chain_circuit_output: BlockHeaderCircuitOutput::try_from_slice(&journal).unwrap(),
+        method_id,
+        policy,
+        confirmation_headers: vec![],
+    };
+    (input, ReceiptClaim::ok(method_id, journal))
+}
+
+fn signed_input(
+    seal: &CircuitGroth16Proof,
+    chain_state: &SignedChainState,
+    verifier: &Risc0VerifierEntry,
+    secret_key: &[u8],
+) -> WinternitzCircuitInput {
+    let seal_encoding = CompressedSealEncoding::Ark;
+    let message = encode_signed_message(seal, chain_state, seal_encoding).unwrap();
+    let params = Parameters::new(message.len() as u32, 8);
+    WinternitzCircuitInput {
+        pub_key: generate_public_key(&params, &secret_key.to_vec()),
+        signature: sign_digits(&params, &secret_key.to_vec(), &message),
+        params,
+        message,
+        control_root: verifier.control_root,
+        seal_encoding,
+    }
+}
+
+fn test_chain_state() -> SignedChainState {
+    SignedChainState {
+        total_work: [1; 16],
+        best_block_hash: [2; 32],
+        block_height: 3,
+        policy_digest: WorkOnlyPolicy::default().digest(),
+    }
+}
+
+/// A well-formed seal that proves nothing.
+fn test_seal() -> CircuitGroth16Proof {
+    let mut rng = ark_std::test_rng();
+    CircuitGroth16Proof::new(
+        ark_bn254::G1Affine::rand(&mut rng),
+        ark_bn254::G2Affine::rand(&mut rng),
+        ark_bn254::G1Affine::rand(&mut rng),
+    )
+}
+
+#[test]
+fn test_header_chain_commits_best_block() {
+    let (_, journal) = header_chain_output();
+    let output = BlockHeaderCircuitOutput::try_from_slice(&journal).unwrap();
+    let headers = regtest_headers();
+
+    assert_eq!(output.chain_state.block_height, HEADER_COUNT as u32 - 1);
+    assert_eq!(
+        output.chain_state.best_block_hash,
+        headers.last().unwrap().compute_block_hash()
+    );
+}
+
+#[test]
+fn test_work_only_commits_work_u128() {
+    let (input, claim) = work_only_input(WorkOnlyPolicy::default());
+    let chain_state = input.header_chain_circuit_output.chain_state.clone();
+    let session = execute(&elf("work-only"), &input, vec![claim]).unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+
+    let output: WorkOnlyCircuitOutput = decode_journal(&session.journal.bytes).unwrap();
+    let work = u128::from_be_bytes(chain_state.total_work[16..].try_into().unwrap());
+    assert_eq!(
+        output.work_u128,
+        [
+            (work >> 96) as u32,
+            (work >> 64) as u32,
+            (work >> 32) as u32,
+            work as u32
+        ]
+    );
+    assert_eq!(output.best_block_hash, chain_state.best_block_hash);
+    assert_eq!(output.block_height, chain_state.block_height);
+    assert_eq!(output.header_chain_method_id, input.method_id);
+}
+
+#[test]
+fn test_work_only_rejects_unpinned_header_chain() {
+    let (mut input, claim) = work_only_input(WorkOnlyPolicy::default());
+    input.method_id = [0; 8];
+    assert_exit(
+        execute(&elf("work-only"), &input, vec![claim]),
+        GuestExitCode::InvalidInput,
+    );
+}
+
+#[test]
+fn test_work_only_rejects_policy_violation() {
+    let (input, claim) = work_only_input(WorkOnlyPolicy {
+        min_height: HEADER_COUNT as u32,
+        ..Default::default()
+    });
+    assert_exit(
+        execute(&elf("work-only"), &input, vec![claim]),
+        GuestExitCode::PolicyViolated,
+    );
+}
+
+#[test]
+fn test_work_only_fails_without_header_chain_receipt() {
+    let (input, _) = work_only_input(WorkOnlyPolicy::default());
+    assert!(execute(&elf("work-only"), &input, vec![]).is_err());
+}
+
+#[test]
+fn test_work_only_fails_on_forged_header_chain_output() {
+    let (mut input, claim) = work_only_input(WorkOnlyPolicy::default());
+    input.header_chain_circuit_output.chain_state.total_work = [0xff; 32];
+    assert!(execute(&elf("work-only"), &input, vec![claim]).is_err());
+}
+
+#[test]
+fn test_work_only_rejects_malformed_input() {
+    assert_exit(
+        execute_raw(&elf("work-only"), &[0; 3], vec![]),
+        GuestExitCode::InvalidInput,
+    );
+}
+
+#[test]
+fn test_winternitz_rejects_invalid_seal() {
+    let input = signed_input(
+        &test_seal(),
+        &test_chain_state(),
+        latest_verifier_entry(),
+        &[1, 2, 3, 4],
+    );
+    assert_exit(
+        execute(
+            &elf("winternitz"),
+            &WinternitzGuestInput::Single(input),
+            vec![],
+        ),
+        GuestExitCode::VerificationFailed,
+    );
+}
+
+#[test]
+fn test_winternitz_batch_rejects_invalid_seal() {
+    let inputs: Vec<WinternitzCircuitInput> = [[1, 2, 3, 4], [5, 6, 7, 8]]
+        .iter()
+        .map(|secret_key| {
+            signed_input(
+                &test_seal(),
+                &test_chain_state(),
+                latest_verifier_entry(),
+                secret_key,
+            )
+        })
+        .collect();
+    assert_exit(
+        execute(
+            &elf("winternitz"),
+            &WinternitzGuestInput::Batch(inputs),
+            vec![],
+        ),
+        GuestExitCode::VerificationFailed,
+    );
+}
+
+#[test]
+fn test_winternitz_rejects_malformed_input() {
+    let mut input = borsh::to_vec(&WinternitzGuestInput::Single(signed_input(
+        &test_seal(),
+        &test_chain_state(),
+        latest_verifier_entry(),
+        &[1, 2, 3, 4],
+    )))
+    .unwrap();
+    input.truncate(input.len() - 1);
+    assert_exit(
+        execute_raw(&elf("winternitz"), &input, vec![]),
+        GuestExitCode::InvalidInput,
+    );
+}
+
+/// Executes the work-only guest under `policy` and signs its output with a dev
+/// seal, as `sign --dev` does.
+fn dev_signed_work_only(policy: WorkOnlyPolicy, secret_key: &[u8]) -> WinternitzCircuitInput {
+    let (input, claim) = work_only_input(policy);
+    let session = execute(&elf("work-only"), &input, vec![claim]).unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    let work_only_output: WorkOnlyCircuitOutput = decode_journal(&session.journal.bytes).unwrap();
+
+    let seal = dev_prove(&work_only_public_inputs(
+        &work_only_output,
+        &DEV_VERIFIER_ENTRY,
+    ))
+    .unwrap();
+    signed_input(
+        &seal,
+        &SignedChainState::from(&work_only_output),
+        &DEV_VERIFIER_ENTRY,
+        secret_key,
+    )
+}
+
+/// Full pipeline: the dev winternitz guest verifies a dev seal over the executed
+/// work-only journal.
+#[test]
+fn test_dev_pipeline() {
+    let policy = WorkOnlyPolicy {
+        min_height: HEADER_COUNT as u32 - 1,
+        ..Default::default()
+    };
+    let input = dev_signed_work_only(policy.clone(), &[1, 2, 3, 4]);
+    let session = execute(
+        &elf("winternitz-dev"),
+        &WinternitzGuestInput::Single(input.clone()),
+        vec![],
+    )
+    .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+
+    let expected = WinternitzCircuitOutput {
+        winternitz_pubkeys_digest: pubkeys_digest(&input.pub_key),
+        policy_digest: policy.digest(),
+    };
+    assert_eq!(session.journal.bytes, encode_journal(&expected));
+}
+
+#[test]
+fn test_dev_pipeline_batch() {
+    let inputs: Vec<WinternitzCircuitInput> = [[1, 2, 3, 4], [5, 6, 7, 8]]
+        .iter()
+        .map(|secret_key| dev_signed_work_only(WorkOnlyPolicy::default(), secret_key))
+        .collect();
+    let session = execute(
+        &elf("winternitz-dev"),
+        &WinternitzGuestInput::Batch(inputs.clone()),
+        vec![],
+    )
+    .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+
+    let expected = WinternitzBatchCircuitOutput {
+        winternitz_pubkeys_digests: inputs
+            .iter()
+            .map(|input| pubkeys_digest(&input.pub_key))
+            .collect(),
+        policy_digests: vec![WorkOnlyPolicy::default().digest(); 2],
+    };
+    assert_eq!(session.journal.bytes, encode_journal(&expected));
+}
+
+/// The dev guest still rejects dev-signed seals that do not verify.
+#[test]
+fn test_dev_winternitz_rejects_invalid_seal() {
+    let input = signed_input(
+        &test_seal(),
+        &test_chain_state(),
+        &DEV_VERIFIER_ENTRY,
+        &[1, 2, 3, 4],
+    );
+    assert_exit(
+        execute(
+            &elf("winternitz-dev"),
+            &WinternitzGuestInput::Single(input),
+            vec![],
+        ),
+        GuestExitCode::VerificationFailed,
+    );
+}
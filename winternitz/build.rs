@@ -33,6 +33,16 @@ fn main() {
                     "BITCOIN_NETWORK={}",
                     std::env::var("BITCOIN_NETWORK").unwrap().as_str()
                 ),
+                "--build-arg",
+                &format!(
+                    "DEV_MODE={}",
+                    std::env::var("WINTERNITZ_DEV_MODE").unwrap_or_default()
+                ),
+                "--build-arg",
+                &format!(
+                    "PROFILING={}",
+                    std::env::var("WINTERNITZ_PROFILING").unwrap_or_default()
+                ),
             ])
             .current_dir(project_root) // Set working directory to project root
             .output()
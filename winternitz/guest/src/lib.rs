@@ -1,152 +1,389 @@
 use ark_bn254::{Bn254, Fr};
-use ark_groth16::PreparedVerifyingKey;
-use ark_serialize::CanonicalDeserialize;
-use constants::{
-    A0_ARK, A1_ARK, ASSUMPTIONS, BN_254_CONTROL_ID_ARK, CLAIM_TAG, INPUT, OUTPUT_TAG, POST_STATE,
-    PREPARED_VK, PRE_STATE,
+use winternitz_core::claim::work_only_public_inputs;
+use winternitz_core::groth16::{batch_verify, CircuitGroth16Proof};
+use winternitz_core::journal::Journal;
+use winternitz_core::message::{decode_signed_message, SignedChainState};
+use winternitz_core::registry::{
+    find_verifier_entry, pinned_header_chain_method_id, pinned_network_magic, Risc0VerifierEntry,
 };
-use hex::ToHex;
-use risc0_zkvm::guest::env;
-use sha2::{Digest, Sha256};
-use std::str::FromStr;
-use winternitz_core::utils::hash160;
 use winternitz_core::winternitz::{
-    verify_signature, WinternitzCircuitInput, WinternitzCircuitOutput,
+    pubkeys_digest, verify_signature, WinternitzBatchCircuitOutput, WinternitzCircuitInput,
+    WinternitzCircuitOutput, WinternitzGuestInput,
 };
-use winternitz_core::zkvm::ZkvmGuest;
-use winternitz_core::{groth16::CircuitGroth16Proof, utils::to_decimal};
-mod constants;
-
-pub fn create_output_digest(total_work: &[u8; 16]) -> [u8; 32] {
-    let total_work_digest: [u8; 32] = Sha256::digest(total_work).into();
-    let len_output: [u8; 2] = hex::decode("0200").unwrap().try_into().unwrap();
-
-    let output_pre_digest: [u8; 98] = [
-        &OUTPUT_TAG,
-        &total_work_digest[..],
-        &ASSUMPTIONS[..],
-        &len_output[..],
-    ]
-    .concat()
-    .try_into()
-    .expect("slice has correct length");
-
-    Sha256::digest(output_pre_digest).into()
-}
-
-pub fn create_claim_digest(output_digest: &[u8; 32]) -> [u8; 32] {
-    let data: [u8; 8] = [0; 8];
-
-    let claim_len: [u8; 2] = [4, 0];
-
-    let concatenated = [
-        &CLAIM_TAG,
-        &INPUT,
-        &PRE_STATE,
-        &POST_STATE,
-        output_digest,
-        &data[..],
-        &claim_len,
-    ]
-    .concat();
-
-    let mut claim_digest = Sha256::digest(concatenated);
-    claim_digest.reverse();
+use winternitz_core::zkvm::{GuestExitCode, ZkvmGuest};
+use winternitz_core::WorkOnlyCircuitOutput;
 
-    claim_digest.into()
-}
-pub struct CircuitGroth16WithTotalWork {
+pub struct CircuitGroth16WithChainState {
     groth16_seal: CircuitGroth16Proof,
-    total_work: [u8; 16],
+    chain_state: SignedChainState,
 }
 
-impl CircuitGroth16WithTotalWork {
+impl CircuitGroth16WithChainState {
     pub fn new(
         groth16_seal: CircuitGroth16Proof,
-        total_work: [u8; 16],
-    ) -> CircuitGroth16WithTotalWork {
-        CircuitGroth16WithTotalWork {
+        chain_state: SignedChainState,
+    ) -> CircuitGroth16WithChainState {
+        CircuitGroth16WithChainState {
             groth16_seal,
-            total_work,
+            chain_state,
         }
     }
 
-    pub fn verify(&self) -> bool {
+    pub fn public_inputs(&self, verifier: &Risc0VerifierEntry) -> Vec<Fr> {
+        // Rebuild the work-only journal, which also pins the header-chain circuit
+        // and network.
+        let work_only_output = WorkOnlyCircuitOutput {
+            work_u128: borsh::from_slice(&self.chain_state.total_work).unwrap(),
+            best_block_hash: self.chain_state.best_block_hash,
+            block_height: self.chain_state.block_height,
+            header_chain_method_id: pinned_header_chain_method_id(),
+            network_magic: pinned_network_magic(),
+            policy_digest: self.chain_state.policy_digest,
+        };
+        work_only_public_inputs(&work_only_output, verifier)
+    }
+
+    /// A verifier setup or proof that can't be used counts as not verifying.
+    pub fn verify(&self, guest: &impl ZkvmGuest, verifier: &Risc0VerifierEntry) -> bool {
         let ark_proof = self.groth16_seal.into();
-        let start = env::cycle_count();
-        let prepared_vk: PreparedVerifyingKey<ark_ec::bn::Bn<ark_bn254::Config>> =
-            CanonicalDeserialize::deserialize_uncompressed(PREPARED_VK).unwrap();
-        let end = env::cycle_count();
-        println!("PVK: {}", end - start);
-        let start = env::cycle_count();
+        let prepared_vk = {
+            let _span = guest.span("PVK");
+            verifier.prepared_vk()
+        };
+        let Ok(prepared_vk) = prepared_vk else {
+            return false;
+        };
+
+        let public_inputs = {
+            let _span = guest.span("PPI");
+            self.public_inputs(verifier)
+        };
+        ark_groth16::Groth16::<Bn254>::verify_proof(&prepared_vk, &ark_proof, &public_inputs)
+            .unwrap_or(false)
+    }
 
-        let output_digest = create_output_digest(&self.total_work);
+    /// Verifies proofs made against the same verifier setup with a single batched
+    /// pairing check, failing like [`CircuitGroth16WithChainState::verify`].
+    pub fn verify_batch(
+        guest: &impl ZkvmGuest,
+        proofs: &[CircuitGroth16WithChainState],
+        verifier: &Risc0VerifierEntry,
+    ) -> bool {
+        let prepared_vk = {
+            let _span = guest.span("PVK");
+            verifier.prepared_vk()
+        };
+        let Ok(prepared_vk) = prepared_vk else {
+            return false;
+        };
 
-        let claim_digest: [u8; 32] = create_claim_digest(&output_digest);
+        let proofs_with_inputs: Vec<(CircuitGroth16Proof, Vec<Fr>)> = {
+            let _span = guest.span("PPI");
+            proofs
+                .iter()
+                .map(|proof| (proof.groth16_seal, proof.public_inputs(verifier)))
+                .collect()
+        };
+        batch_verify(&prepared_vk, &proofs_with_inputs).unwrap_or(false)
+    }
+}
 
-        let claim_digest_hex: String = claim_digest.encode_hex();
-        let c0_str = &claim_digest_hex[32..64];
-        let c1_str = &claim_digest_hex[0..32];
+/// Checks the Winternitz signature of `input` and decodes the signed seal and chain state.
+pub fn verify_winternitz_and_decode(
+    guest: &impl ZkvmGuest,
+    input: &WinternitzCircuitInput,
+) -> Option<(&'static Risc0VerifierEntry, CircuitGroth16WithChainState)> {
+    let verifier = find_verifier_entry(&input.control_root)?;
 
-        let c0_dec = to_decimal(c0_str).unwrap();
-        let c1_dec = to_decimal(c1_str).unwrap();
+    let signature_valid = {
+        let _span = guest.span("WNV");
+        verify_signature(input)
+    };
+    if !signature_valid {
+        return None;
+    }
 
-        let c0 = Fr::from_str(&c0_dec).unwrap();
-        let c1 = Fr::from_str(&c1_dec).unwrap();
+    let (seal, chain_state) = decode_signed_message(&input.message, input.seal_encoding).ok()?;
 
-        let public_inputs = vec![A0_ARK, A1_ARK, c0, c1, BN_254_CONTROL_ID_ARK];
+    Some((
+        verifier,
+        CircuitGroth16WithChainState::new(seal, chain_state),
+    ))
+}
 
-        let end = env::cycle_count();
-        println!("PPI: {}", end - start);
-        ark_groth16::Groth16::<Bn254>::verify_proof(&prepared_vk, &ark_proof, &public_inputs)
-            .unwrap()
-    }
+/// Returns the signed chain state when both the signature and the seal verify.
+pub fn verify_winternitz_and_groth16(
+    guest: &impl ZkvmGuest,
+    input: &WinternitzCircuitInput,
+) -> Option<SignedChainState> {
+    let (verifier, groth16_proof) = verify_winternitz_and_decode(guest, input)?;
+
+    let _span = guest.span("G16V");
+    let verified = groth16_proof.verify(guest, verifier);
+    verified.then_some(groth16_proof.chain_state)
 }
 
-pub fn verify_winternitz_and_groth16(input: &WinternitzCircuitInput) -> bool {
-    let start = env::cycle_count();
-    if !verify_signature(&input) {
-        return false;
+/// Batched counterpart of [`verify_winternitz_and_groth16`]. Every signature is
+/// checked on its own; the Groth16 proofs are verified in one batch per verifier setup.
+/// Returns the signed chain states in input order.
+pub fn verify_winternitz_and_groth16_batch(
+    guest: &impl ZkvmGuest,
+    inputs: &[WinternitzCircuitInput],
+) -> Option<Vec<SignedChainState>> {
+    if inputs.is_empty() {
+        return None;
     }
 
-    let end = env::cycle_count();
-    println!("WNV: {}", end - start);
-    let compressed_seal: [u8; 128] = match input.message[0..128].try_into() {
-        Ok(compressed_seal) => compressed_seal,
-        Err(_) => return false,
-    };
-    let total_work: [u8; 16] = match input.message[128..144].try_into() {
-        Ok(total_work) => total_work,
-        Err(_) => return false,
-    };
+    let mut chain_states = Vec::with_capacity(inputs.len());
+    let mut batches: Vec<(&Risc0VerifierEntry, Vec<CircuitGroth16WithChainState>)> = Vec::new();
+    for input in inputs {
+        let (verifier, groth16_proof) = verify_winternitz_and_decode(guest, input)?;
+        chain_states.push(groth16_proof.chain_state.clone());
+        match batches.iter_mut().find(|(entry, _)| *entry == verifier) {
+            Some((_, proofs)) => proofs.push(groth16_proof),
+            None => batches.push((verifier, vec![groth16_proof])),
+        }
+    }
 
-    let seal = match CircuitGroth16Proof::from_compressed(&compressed_seal) {
-        Ok(seal) => seal,
-        Err(_) => return false,
+    let _span = guest.span("G16V");
+    let verified = batches.iter().all(|(verifier, proofs)| {
+        CircuitGroth16WithChainState::verify_batch(guest, proofs, verifier)
+    });
+    verified.then_some(chain_states)
+}
+
+/// Aborts with a [`GuestExitCode`] when the input can't be decoded or doesn't
+/// verify, so only verified seals produce a provable journal.
+pub fn winternitz_circuit(guest: &impl ZkvmGuest) {
+    // The span ends before the guest exits, so failed runs still report WNT.
+    let outcome = {
+        let _span = guest.span("WNT");
+        verify_and_commit(guest)
     };
+    if let Err(code) = outcome {
+        guest.exit(code)
+    }
+}
+
+fn verify_and_commit(guest: &impl ZkvmGuest) -> Result<(), GuestExitCode> {
+    let input: WinternitzGuestInput = guest
+        .try_read_from_host()
+        .map_err(|_| GuestExitCode::InvalidInput)?;
 
-    let groth16_proof = CircuitGroth16WithTotalWork::new(seal, total_work);
-    let start = env::cycle_count();
-    let res = groth16_proof.verify();
-    let end = env::cycle_count();
-    println!("G16V: {}", end - start);
-    println!("{}", res);
-    res
+    let committed = match input {
+        WinternitzGuestInput::Single(input) => {
+            let chain_state = verify_winternitz_and_groth16(guest, &input)
+                .ok_or(GuestExitCode::VerificationFailed)?;
+            guest.try_commit(&Journal(&WinternitzCircuitOutput {
+                winternitz_pubkeys_digest: pubkeys_digest(&input.pub_key),
+                policy_digest: chain_state.policy_digest,
+            }))
+        }
+        WinternitzGuestInput::Batch(inputs) => {
+            let chain_states = verify_winternitz_and_groth16_batch(guest, &inputs)
+                .ok_or(GuestExitCode::VerificationFailed)?;
+            guest.try_commit(&Journal(&WinternitzBatchCircuitOutput {
+                winternitz_pubkeys_digests: inputs
+                    .iter()
+                    .map(|input| pubkeys_digest(&input.pub_key))
+                    .collect(),
+                policy_digests: chain_states
+                    .iter()
+                    .map(|chain_state| chain_state.policy_digest)
+                    .collect(),
+            }))
+        }
+    };
+    committed.map_err(|_| GuestExitCode::CommitFailed)
 }
 
-pub fn winternitz_circuit(guest: &impl ZkvmGuest) {
-    let start = env::cycle_count();
-    let input: WinternitzCircuitInput = guest.read_from_host();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use winternitz_core::dev::{dev_prove, DEV_VERIFIER_ENTRY};
+    use winternitz_core::groth16::CompressedSealEncoding;
+    use winternitz_core::journal::encode_journal;
+    use winternitz_core::message::encode_signed_message;
+    use winternitz_core::mock::MockGuest;
+    use winternitz_core::policy::WorkOnlyPolicy;
+    use winternitz_core::registry::latest_verifier_entry;
+    use winternitz_core::winternitz::{generate_public_key, sign_digits, Parameters};
 
-    verify_winternitz_and_groth16(&input);
-    let mut pub_key_concat: Vec<u8> = vec![0; input.pub_key.len() * 20];
-    for (i, pubkey) in input.pub_key.iter().enumerate() {
-        pub_key_concat[i * 20..(i + 1) * 20].copy_from_slice(pubkey);
+    fn random_seal() -> CircuitGroth16Proof {
+        let mut rng = ark_std::test_rng();
+        CircuitGroth16Proof::new(
+            ark_bn254::G1Affine::rand(&mut rng),
+            ark_bn254::G2Affine::rand(&mut rng),
+            ark_bn254::G1Affine::rand(&mut rng),
+        )
     }
 
-    guest.commit(&WinternitzCircuitOutput {
-        winternitz_pubkeys_digest: hash160(&pub_key_concat),
-    });
-    let end = env::cycle_count();
-    println!("WNT: {}", end - start);
+    fn test_chain_state() -> SignedChainState {
+        SignedChainState {
+            total_work: [1u8; 16],
+            best_block_hash: [2u8; 32],
+            block_height: 3,
+            policy_digest: WorkOnlyPolicy::default().digest(),
+        }
+    }
+
+    fn sign_input(
+        seal: &CircuitGroth16Proof,
+        chain_state: &SignedChainState,
+        control_root: [u8; 32],
+    ) -> WinternitzCircuitInput {
+        let seal_encoding = CompressedSealEncoding::Ark;
+        let message = encode_signed_message(seal, chain_state, seal_encoding).unwrap();
+
+        let params = Parameters::new(message.len() as u32, 8);
+        let secret_key = vec![1, 2, 3, 4];
+        WinternitzCircuitInput {
+            pub_key: generate_public_key(&params, &secret_key),
+            signature: sign_digits(&params, &secret_key, &message),
+            params,
+            message,
+            control_root,
+            seal_encoding,
+        }
+    }
+
+    fn signed_input() -> WinternitzCircuitInput {
+        sign_input(
+            &random_seal(),
+            &test_chain_state(),
+            latest_verifier_entry().control_root,
+        )
+    }
+
+    /// Input with a seal of the dev setup, which verifies.
+    fn dev_signed_input() -> WinternitzCircuitInput {
+        let chain_state = test_chain_state();
+        let public_inputs = CircuitGroth16WithChainState::new(random_seal(), chain_state.clone())
+            .public_inputs(&DEV_VERIFIER_ENTRY);
+        let seal = dev_prove(&public_inputs).unwrap();
+        sign_input(&seal, &chain_state, DEV_VERIFIER_ENTRY.control_root)
+    }
+
+    fn run(input: &WinternitzGuestInput) -> MockGuest {
+        let guest = MockGuest::new(borsh::to_vec(input).unwrap());
+        winternitz_circuit(&guest);
+        guest
+    }
+
+    #[test]
+    fn test_rejects_unknown_control_root() {
+        let guest = MockGuest::new(vec![]);
+        let mut input = signed_input();
+        assert!(verify_winternitz_and_decode(&guest, &input).is_some());
+
+        input.control_root = [0u8; 32];
+        assert!(verify_winternitz_and_decode(&guest, &input).is_none());
+    }
+
+    #[test]
+    fn test_rejects_invalid_groth16_proof() {
+        let guest = MockGuest::new(vec![]);
+        let input = signed_input();
+        assert!(verify_winternitz_and_groth16(&guest, &input).is_none());
+        assert!(verify_winternitz_and_groth16_batch(&guest, &[input.clone(), input]).is_none());
+    }
+
+    #[test]
+    fn test_rejects_malformed_verifier_setup() {
+        let guest = MockGuest::new(vec![]);
+        let verifier = Risc0VerifierEntry {
+            prepared_vk: &[1, 2, 3],
+            ..DEV_VERIFIER_ENTRY
+        };
+        let proof = CircuitGroth16WithChainState::new(random_seal(), test_chain_state());
+        assert!(!proof.verify(&guest, &verifier));
+        assert!(!CircuitGroth16WithChainState::verify_batch(
+            &guest,
+            &[proof],
+            &verifier
+        ));
+    }
+
+    #[test]
+    fn test_accepts_dev_seal() {
+        let guest = MockGuest::new(vec![]);
+        let mut input = dev_signed_input();
+        assert_eq!(
+            verify_winternitz_and_groth16(&guest, &input),
+            Some(test_chain_state())
+        );
+        assert_eq!(
+            verify_winternitz_and_groth16_batch(&guest, &[input.clone(), input.clone()]),
+            Some(vec![test_chain_state(), test_chain_state()])
+        );
+
+        // The same seal signed for the production setup does not verify.
+        input = sign_input(
+            &decode_signed_message(&input.message, input.seal_encoding)
+                .unwrap()
+                .0,
+            &test_chain_state(),
+            latest_verifier_entry().control_root,
+        );
+        assert!(verify_winternitz_and_groth16(&guest, &input).is_none());
+    }
+
+    #[test]
+    fn test_winternitz_circuit_commits_pubkeys_digest() {
+        let input = dev_signed_input();
+        let guest = run(&WinternitzGuestInput::Single(input.clone()));
+
+        let expected = WinternitzCircuitOutput {
+            winternitz_pubkeys_digest: pubkeys_digest(&input.pub_key),
+            policy_digest: test_chain_state().policy_digest,
+        };
+        assert_eq!(guest.journal(), encode_journal(&expected));
+    }
+
+    #[test]
+    fn test_winternitz_circuit_commits_batch_pubkeys_digests() {
+        let inputs = vec![dev_signed_input(), dev_signed_input()];
+        let guest = run(&WinternitzGuestInput::Batch(inputs.clone()));
+
+        let expected = WinternitzBatchCircuitOutput {
+            winternitz_pubkeys_digests: inputs
+                .iter()
+                .map(|input| pubkeys_digest(&input.pub_key))
+                .collect(),
+            policy_digests: vec![test_chain_state().policy_digest; 2],
+        };
+        assert_eq!(guest.journal(), encode_journal(&expected));
+    }
+
+    #[test]
+    #[should_panic(expected = "VerificationFailed")]
+    fn test_winternitz_circuit_rejects_invalid_seal() {
+        run(&WinternitzGuestInput::Single(signed_input()));
+    }
+
+    #[test]
+    #[should_panic(expected = "VerificationFailed")]
+    fn test_winternitz_circuit_rejects_invalid_signature() {
+        let mut input = dev_signed_input();
+        input.signature[0][0] ^= 1;
+        run(&WinternitzGuestInput::Single(input));
+    }
+
+    #[test]
+    #[should_panic(expected = "VerificationFailed")]
+    fn test_winternitz_circuit_rejects_batch_with_invalid_seal() {
+        run(&WinternitzGuestInput::Batch(vec![
+            dev_signed_input(),
+            signed_input(),
+        ]));
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidInput")]
+    fn test_winternitz_circuit_aborts_on_malformed_input() {
+        let mut input = borsh::to_vec(&WinternitzGuestInput::Single(signed_input())).unwrap();
+        input.truncate(input.len() - 1);
+        winternitz_circuit(&MockGuest::new(input));
+    }
 }
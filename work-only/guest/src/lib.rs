@@ -1,21 +1,162 @@
 use crypto_bigint::{Encoding, U128, U256};
-use risc0_zkvm::guest::env;
-use winternitz_core::{zkvm::ZkvmGuest, WorkOnlyCircuitInput, WorkOnlyCircuitOutput};
+use winternitz_core::journal::Journal;
+use winternitz_core::registry::{pinned_header_chain_method_id, pinned_network_magic};
+use winternitz_core::zkvm::{GuestExitCode, ZkvmGuest};
+use winternitz_core::{WorkOnlyCircuitInput, WorkOnlyCircuitOutput};
 
+/// Aborts with a [`GuestExitCode`] on malformed input: there is no meaningful work
+/// to commit without a verified header chain output.
 pub fn work_only_circuit(guest: &impl ZkvmGuest) {
-    let start = env::cycle_count();
-    let input: WorkOnlyCircuitInput = guest.read_from_host();
-    env::verify(
-        input.method_id,
-        &borsh::to_vec(&input.header_chain_circuit_output).unwrap(),
-    )
-    .unwrap();
-    let total_work_u256: U256 =
-        U256::from_be_bytes(input.header_chain_circuit_output.chain_state.total_work);
-    let (_, chain_state_total_work_u128): (U128, U128) = total_work_u256.into();
+    let _span = guest.span("WO");
+    let input: WorkOnlyCircuitInput = match guest.try_read_from_host() {
+        Ok(input) => input,
+        Err(_) => guest.exit(GuestExitCode::InvalidInput),
+    };
+    // Any guest committing a well-formed header chain output would pass `verify`,
+    // so only the pinned header-chain circuit is accepted.
+    let header_chain_method_id = pinned_header_chain_method_id();
+    if input.method_id != header_chain_method_id {
+        guest.exit(GuestExitCode::InvalidInput)
+    }
+    if guest
+        .try_verify(input.method_id, &input.header_chain_circuit_output)
+        .is_err()
+    {
+        guest.exit(GuestExitCode::AssumptionFailed)
+    }
+    let chain_state = &input.header_chain_circuit_output.chain_state;
+    let confirmation_headers: Vec<([u8; 32], [u8; 32])> = input
+        .confirmation_headers
+        .iter()
+        .map(|header| (header.prev_block_hash, header.compute_block_hash()))
+        .collect();
+    let policy_met = input.policy.check(
+        &chain_state.total_work,
+        chain_state.block_height,
+        &chain_state.best_block_hash,
+        &confirmation_headers,
+    );
+    if policy_met.is_err() {
+        guest.exit(GuestExitCode::PolicyViolated)
+    }
+    let total_work_u256: U256 = U256::from_be_bytes(chain_state.total_work);
+    let (high, chain_state_total_work_u128): (U128, U128) = total_work_u256.into();
+    // Only the low 128 bits are committed, refuse to prove rather than wrap.
+    if high != U128::ZERO {
+        guest.exit(GuestExitCode::Overflow)
+    }
     let mut words = chain_state_total_work_u128.to_words();
     words.reverse();
-    guest.commit(&WorkOnlyCircuitOutput { work_u128: words });
-    let end = env::cycle_count();
-    println!("WO: {}", end - start);
+    let output = WorkOnlyCircuitOutput {
+        work_u128: words,
+        best_block_hash: chain_state.best_block_hash,
+        block_height: chain_state.block_height,
+        header_chain_method_id,
+        network_magic: pinned_network_magic(),
+        policy_digest: input.policy.digest(),
+    };
+    if guest.try_commit(&Journal(&output)).is_err() {
+        guest.exit(GuestExitCode::CommitFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use header_chain::header_chain::{BlockHeaderCircuitOutput, ChainState};
+    use winternitz_core::journal::encode_journal;
+    use winternitz_core::mock::MockGuest;
+    use winternitz_core::policy::WorkOnlyPolicy;
+
+    fn total_work(high: u128, low: u128) -> [u8; 32] {
+        let mut total_work = [0u8; 32];
+        total_work[..16].copy_from_slice(&high.to_be_bytes());
+        total_work[16..].copy_from_slice(&low.to_be_bytes());
+        total_work
+    }
+
+    fn work_only_input(total_work: [u8; 32], policy: WorkOnlyPolicy) -> WorkOnlyCircuitInput {
+        let mut chain_state = ChainState::new();
+        chain_state.block_height = 9;
+        chain_state.total_work = total_work;
+        chain_state.best_block_hash = [2; 32];
+        WorkOnlyCircuitInput {
+            header_chain_circuit_output: BlockHeaderCircuitOutput {
+                method_id: pinned_header_chain_method_id(),
+                genesis_state_hash: [1; 32],
+                chain_state,
+            },
+            method_id: pinned_header_chain_method_id(),
+            policy,
+            confirmation_headers: vec![],
+        }
+    }
+
+    /// Runs the circuit with the header-chain output as a verified assumption of
+    /// `input.method_id`.
+    fn run(input: &WorkOnlyCircuitInput) -> MockGuest {
+        let guest = MockGuest::new(borsh::to_vec(input).unwrap()).with_assumption(
+            input.method_id,
+            borsh::to_vec(&input.header_chain_circuit_output).unwrap(),
+        );
+        work_only_circuit(&guest);
+        guest
+    }
+
+    #[test]
+    fn test_work_only_circuit_commits_output() {
+        let work = 0x0000_0001_0000_0002_0000_0003_0000_0004;
+        let policy = WorkOnlyPolicy {
+            min_height: 9,
+            ..Default::default()
+        };
+        let guest = run(&work_only_input(total_work(0, work), policy.clone()));
+
+        let expected = WorkOnlyCircuitOutput {
+            work_u128: [1, 2, 3, 4],
+            best_block_hash: [2; 32],
+            block_height: 9,
+            header_chain_method_id: pinned_header_chain_method_id(),
+            network_magic: pinned_network_magic(),
+            policy_digest: policy.digest(),
+        };
+        assert_eq!(guest.journal(), encode_journal(&expected));
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidInput")]
+    fn test_work_only_circuit_rejects_unpinned_method_id() {
+        let mut input = work_only_input(total_work(0, 1), WorkOnlyPolicy::default());
+        input.method_id = [0; 8];
+        input.header_chain_circuit_output.method_id = [0; 8];
+        run(&input);
+    }
+
+    #[test]
+    #[should_panic(expected = "AssumptionFailed")]
+    fn test_work_only_circuit_requires_header_chain_receipt() {
+        let input = work_only_input(total_work(0, 1), WorkOnlyPolicy::default());
+        work_only_circuit(&MockGuest::new(borsh::to_vec(&input).unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_work_only_circuit_rejects_work_above_128_bits() {
+        run(&work_only_input(
+            total_work(1, 0),
+            WorkOnlyPolicy::default(),
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "PolicyViolated")]
+    fn test_work_only_circuit_rejects_policy_violation() {
+        run(&work_only_input(
+            total_work(0, 1),
+            WorkOnlyPolicy {
+                min_height: 10,
+                ..Default::default()
+            },
+        ));
+    }
 }